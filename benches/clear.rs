@@ -0,0 +1,39 @@
+//! Times `Context::clear` for framebuffers around the `rayon` threshold (1M pixels).
+//!
+//! Run it with and without the `rayon` feature to compare the serial and the parallel fill:
+//!
+//! ```sh
+//! cargo bench --bench clear --features headless
+//! cargo bench --bench clear --features headless,rayon
+//! ```
+
+use simple_pixels::Context;
+use std::{hint::black_box, time::Instant};
+
+const ITERATIONS: u32 = 200;
+
+fn main() {
+    for (width, height) in [
+        (512, 512),
+        (1024, 768),
+        (1024, 1024),
+        (1920, 1080),
+        (3840, 2160),
+    ] {
+        let mut ctx = Context::new_headless(width, height);
+        ctx.clear();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            ctx.clear();
+            black_box(&mut ctx);
+        }
+        let per_clear = start.elapsed() / ITERATIONS;
+
+        println!(
+            "{width}x{height} ({} pixels): {per_clear:?} per clear, {:.3} ns/pixel",
+            width * height,
+            per_clear.as_nanos() as f64 / (width * height) as f64,
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use miniquad::{KeyCode, MouseButton};
+use rustc_hash::FxHashMap;
+
+/// An input that can be bound to an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// A keyboard key.
+    Key(KeyCode),
+    /// A mouse button.
+    MouseButton(MouseButton),
+}
+
+impl From<KeyCode> for Binding {
+    #[inline]
+    fn from(key: KeyCode) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<MouseButton> for Binding {
+    #[inline]
+    fn from(button: MouseButton) -> Self {
+        Self::MouseButton(button)
+    }
+}
+
+/// Named actions bound to keys and mouse buttons.
+///
+/// The map used for the input queries lives in the [`Context`](crate::Context),
+/// see [`Context::action_map_mut()`](crate::Context::action_map_mut) and [`Context::is_action_down()`](crate::Context::is_action_down).
+///
+/// ```
+/// # use simple_pixels::{miniquad::KeyCode, ActionMap, Binding};
+/// let mut actions = ActionMap::new();
+/// actions.bind("jump", KeyCode::Space).bind("jump", KeyCode::W);
+///
+/// assert_eq!(
+///     actions.bindings("jump"),
+///     [Binding::Key(KeyCode::Space), Binding::Key(KeyCode::W)]
+/// );
+/// assert!(actions.bindings("fire").is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ActionMap {
+    actions: FxHashMap<String, Vec<Binding>>,
+}
+
+impl ActionMap {
+    /// Create an empty action map.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind an input to an action. An action can have any number of inputs, binding the same one twice does nothing.
+    pub fn bind(&mut self, action: &str, input: impl Into<Binding>) -> &mut Self {
+        let input = input.into();
+        let bindings = self.actions.entry(action.to_string()).or_default();
+
+        if !bindings.contains(&input) {
+            bindings.push(input);
+        }
+
+        self
+    }
+
+    /// Remove an input from an action.
+    pub fn unbind(&mut self, action: &str, input: impl Into<Binding>) -> &mut Self {
+        let input = input.into();
+
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|&binding| binding != input);
+        }
+
+        self
+    }
+
+    /// Remove all inputs from an action.
+    #[inline]
+    pub fn clear_action(&mut self, action: &str) -> &mut Self {
+        self.actions.remove(action);
+        self
+    }
+
+    /// Returns the inputs bound to an action in the order they were bound.
+    #[inline]
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+}
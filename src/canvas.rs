@@ -0,0 +1,89 @@
+use crate::DrawTarget;
+use rgb::RGBA8;
+
+/// An off-screen buffer of pixels that can be drawn on like the framebuffer.
+///
+/// Can be used for composing layers, draw it with [`DrawTarget::draw_canvas()`].
+/// Drawing on a canvas is never clipped (except to its size) and always uses [`BlendMode::Replace`](crate::BlendMode::Replace).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    pixels: Vec<RGBA8>,
+    width: u32,
+    height: u32,
+}
+
+impl Canvas {
+    /// Create a new canvas filled with `fill`.
+    #[inline]
+    pub fn new(width: u32, height: u32, fill: RGBA8) -> Self {
+        Self {
+            pixels: vec![fill; width as usize * height as usize],
+            width,
+            height,
+        }
+    }
+
+    /// Create a canvas from existing pixels (row-major order).
+    ///
+    /// Returns `None` if the length of `pixels` isn't `width * height`.
+    #[inline]
+    pub fn from_pixels(pixels: Vec<RGBA8>, width: u32, height: u32) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize).then_some(Self {
+            pixels,
+            width,
+            height,
+        })
+    }
+
+    /// Canvas width (in pixels).
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Canvas height (in pixels).
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the canvas' pixels (row-major order).
+    #[inline]
+    pub fn pixels(&self) -> &[RGBA8] {
+        &self.pixels
+    }
+
+    /// Returns the canvas' pixels (row-major order).
+    #[inline]
+    pub fn pixels_mut(&mut self) -> &mut [RGBA8] {
+        &mut self.pixels
+    }
+
+    /// Returns the canvas' pixels, consuming the canvas.
+    #[inline]
+    pub fn into_pixels(self) -> Vec<RGBA8> {
+        self.pixels
+    }
+}
+
+impl DrawTarget for Canvas {
+    #[inline]
+    fn buffer_width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    fn buffer_height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    fn get_draw_buffer(&self) -> &[RGBA8] {
+        &self.pixels
+    }
+
+    #[inline]
+    fn get_mut_draw_buffer(&mut self) -> &mut [RGBA8] {
+        &mut self.pixels
+    }
+}
@@ -0,0 +1,150 @@
+//! Color constructors and named colors.
+
+use rgb::RGBA8;
+
+/// Fully transparent black.
+pub const TRANSPARENT: RGBA8 = RGBA8::new(0, 0, 0, 0);
+/// Opaque black.
+pub const BLACK: RGBA8 = RGBA8::new(0, 0, 0, 255);
+/// Opaque white.
+pub const WHITE: RGBA8 = RGBA8::new(255, 255, 255, 255);
+/// Opaque gray (50% brightness).
+pub const GRAY: RGBA8 = RGBA8::new(128, 128, 128, 255);
+/// Opaque red.
+pub const RED: RGBA8 = RGBA8::new(255, 0, 0, 255);
+/// Opaque green.
+pub const GREEN: RGBA8 = RGBA8::new(0, 255, 0, 255);
+/// Opaque blue.
+pub const BLUE: RGBA8 = RGBA8::new(0, 0, 255, 255);
+/// Opaque yellow.
+pub const YELLOW: RGBA8 = RGBA8::new(255, 255, 0, 255);
+/// Opaque cyan.
+pub const CYAN: RGBA8 = RGBA8::new(0, 255, 255, 255);
+/// Opaque magenta.
+pub const MAGENTA: RGBA8 = RGBA8::new(255, 0, 255, 255);
+
+/// Create an opaque color.
+#[inline]
+pub const fn rgb(r: u8, g: u8, b: u8) -> RGBA8 {
+    RGBA8::new(r, g, b, 255)
+}
+
+/// Create a color from a `0xRRGGBBAA` number.
+///
+/// ```
+/// use simple_pixels::{color, rgb::RGBA8};
+///
+/// assert_eq!(color::from_hex(0xFF8800FF), RGBA8::new(255, 136, 0, 255));
+/// ```
+#[inline]
+pub const fn from_hex(hex: u32) -> RGBA8 {
+    let [r, g, b, a] = hex.to_be_bytes();
+
+    RGBA8::new(r, g, b, a)
+}
+
+/// Convert a color from HSV to RGBA.
+///
+/// `h` is the hue in degrees (wrapped to `0.0..360.0`),
+/// `s` and `v` are the saturation and value (clamped to `0.0..=1.0`).
+pub fn hsv_to_rgba(h: f32, s: f32, v: f32, a: u8) -> RGBA8 {
+    let h = h.rem_euclid(360.);
+    let s = s.clamp(0., 1.);
+    let v = v.clamp(0., 1.);
+
+    let chroma = v * s;
+    let sector = h / 60.;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let m = v - chroma;
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+
+    let to_u8 = |c: f32| ((c + m) * 255.).round() as u8;
+
+    RGBA8::new(to_u8(r), to_u8(g), to_u8(b), a)
+}
+
+/// Convert a color from RGBA to HSV, ignoring the alpha channel.
+///
+/// Returns `(h, s, v)` where `h` is the hue in degrees (`0.0..360.0`),
+/// `s` and `v` are the saturation and value (`0.0..=1.0`).
+pub fn rgba_to_hsv(color: RGBA8) -> (f32, f32, f32) {
+    let r = color.r as f32 / 255.;
+    let g = color.g as f32 / 255.;
+    let b = color.b as f32 / 255.;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    let h = if chroma == 0. {
+        0.
+    } else if max == r {
+        60. * ((g - b) / chroma).rem_euclid(6.)
+    } else if max == g {
+        60. * ((b - r) / chroma + 2.)
+    } else {
+        60. * ((r - g) / chroma + 4.)
+    };
+
+    let s = if max == 0. { 0. } else { chroma / max };
+
+    // guard against rounding up to exactly 360
+    (if h >= 360. { 0. } else { h }, s, max)
+}
+
+/// Linearly interpolate between two colors, channel by channel (including alpha).
+///
+/// `t` is clamped to `0.0..=1.0`, the channels are rounded to the nearest value.
+///
+/// ```
+/// use simple_pixels::{color, rgb::RGBA8};
+///
+/// let (a, b) = (RGBA8::new(10, 20, 30, 0), RGBA8::new(200, 100, 0, 255));
+///
+/// assert_eq!(color::lerp(a, b, 0.), a);
+/// assert_eq!(color::lerp(a, b, 1.), b);
+/// assert_eq!(color::lerp(a, b, 2.), b);
+/// assert_eq!(color::lerp(color::BLACK, color::WHITE, 0.5), color::rgb(128, 128, 128));
+/// ```
+pub fn lerp(a: RGBA8, b: RGBA8, t: f32) -> RGBA8 {
+    let t = t.clamp(0., 1.);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+    RGBA8::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_round_trip() {
+        let channel = (0..=255).step_by(15).chain([1, 128, 254]);
+
+        for r in channel.clone() {
+            for g in channel.clone() {
+                for b in channel.clone() {
+                    let color = RGBA8::new(r, g, b, 77);
+                    let (h, s, v) = rgba_to_hsv(color);
+                    let round_trip = hsv_to_rgba(h, s, v, color.a);
+
+                    let close = |a: u8, b: u8| a.abs_diff(b) <= 1;
+                    assert!(
+                        close(round_trip.r, r) && close(round_trip.g, g) && close(round_trip.b, b),
+                        "{color:?} -> {:?} -> {round_trip:?}",
+                        (h, s, v)
+                    );
+                    assert_eq!(round_trip.a, color.a);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use miniquad::conf::Conf;
+
+/// A builder for the window configuration passed to [`start()`](crate::start).
+///
+/// Starts with `miniquad`'s defaults: an 800×600 resizable window without a title.
+/// For the options not covered here, build the [`Conf`] and modify it directly.
+///
+/// ```
+/// # use simple_pixels::ConfigBuilder;
+/// let conf = ConfigBuilder::new()
+///     .title("Game")
+///     .window_size(640, 480)
+///     .resizable(false)
+///     .build();
+///
+/// assert_eq!(conf.window_title, "Game");
+/// assert_eq!((conf.window_width, conf.window_height), (640, 480));
+/// assert!(!conf.window_resizable);
+/// ```
+#[derive(Debug, Default)]
+pub struct ConfigBuilder {
+    conf: Conf,
+}
+
+impl ConfigBuilder {
+    /// Create a new builder with the default configuration.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the window title.
+    #[inline]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.conf.window_title = title.into();
+        self
+    }
+
+    /// Set the initial window size (in screen coords).
+    #[inline]
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.conf.window_width = width as _;
+        self.conf.window_height = height as _;
+        self
+    }
+
+    /// Start in fullscreen mode.
+    #[inline]
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.conf.fullscreen = fullscreen;
+        self
+    }
+
+    /// Use the full resolution on high-DPI displays.
+    #[inline]
+    pub fn high_dpi(mut self, high_dpi: bool) -> Self {
+        self.conf.high_dpi = high_dpi;
+        self
+    }
+
+    /// Allow the user to resize the window.
+    #[inline]
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.conf.window_resizable = resizable;
+        self
+    }
+
+    /// Returns the built configuration.
+    #[inline]
+    pub fn build(self) -> Conf {
+        self.conf
+    }
+}
+
+impl From<ConfigBuilder> for Conf {
+    #[inline]
+    fn from(builder: ConfigBuilder) -> Self {
+        builder.build()
+    }
+}
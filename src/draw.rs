@@ -0,0 +1,3104 @@
+use crate::{Canvas, Font, Palette};
+use rgb::RGBA8;
+use simple_blit::{GenericSurface, Point, Size, Surface};
+
+/// How drawn pixels are combined with the pixels already in the framebuffer.
+///
+/// All blending is done in integer math.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrite the framebuffer pixels, ignoring the alpha channel.
+    #[default]
+    Replace,
+    /// Composite the pixels over the framebuffer pixels using their alpha channel (straight alpha).
+    AlphaBlend,
+    /// Add the color channels (multiplied by the alpha) to the framebuffer pixels.
+    /// The framebuffer alpha is kept.
+    Add,
+    /// Multiply the framebuffer color channels by the color channels (weighted by the alpha).
+    /// The framebuffer alpha is kept.
+    Multiply,
+}
+
+/// An error returned by the `try_` drawing functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawError {
+    /// The number of pixels doesn't match the size of the rectangle.
+    PixelCountMismatch {
+        /// `width * height` of the rectangle.
+        expected: usize,
+        /// The length of the pixel slice.
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for DrawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PixelCountMismatch { expected, actual } => {
+                write!(f, "expected {expected} pixels, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DrawError {}
+
+/// Combine `src` with `dst` according to the blend mode.
+#[inline]
+pub(crate) fn blend_into(dst: &mut RGBA8, src: RGBA8, mode: BlendMode) {
+    let alpha = src.a as u32;
+    let scale = |c: u32| (c * alpha + 127) / 255;
+
+    *dst = match mode {
+        BlendMode::Replace => src,
+        BlendMode::AlphaBlend => blend_over(*dst, src),
+        BlendMode::Add => {
+            let add = |src: u8, dst: u8| (dst as u32 + scale(src as u32)).min(255) as u8;
+
+            RGBA8::new(
+                add(src.r, dst.r),
+                add(src.g, dst.g),
+                add(src.b, dst.b),
+                dst.a,
+            )
+        }
+        BlendMode::Multiply => {
+            // interpolate between white (no effect) and the source color first
+            let mul = |src: u8, dst: u8| {
+                let factor = 255 - alpha + scale(src as u32);
+                ((dst as u32 * factor + 127) / 255) as u8
+            };
+
+            RGBA8::new(
+                mul(src.r, dst.r),
+                mul(src.g, dst.g),
+                mul(src.b, dst.b),
+                dst.a,
+            )
+        }
+    };
+}
+
+/// Straight-alpha source-over compositing in integer math.
+#[inline]
+pub(crate) fn blend_over(dst: RGBA8, src: RGBA8) -> RGBA8 {
+    let alpha = src.a as u32;
+    let inv_alpha = 255 - alpha;
+
+    let mix = |src: u8, dst: u8| ((src as u32 * alpha + dst as u32 * inv_alpha + 127) / 255) as u8;
+
+    RGBA8::new(
+        mix(src.r, dst.r),
+        mix(src.g, dst.g),
+        mix(src.b, dst.b),
+        (alpha + (dst.a as u32 * inv_alpha + 127) / 255) as u8,
+    )
+}
+
+/// Interpolate between `a` and `b` at `num / den` in integer math.
+#[inline]
+pub(crate) fn lerp_color(a: RGBA8, b: RGBA8, num: u32, den: u32) -> RGBA8 {
+    if den == 0 {
+        return a;
+    }
+
+    let mix = |a: u8, b: u8| ((a as u32 * (den - num) + b as u32 * num + den / 2) / den) as u8;
+
+    RGBA8::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
+
+/// Buffers with at least this many pixels are filled in parallel.
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 1 << 20;
+
+/// Number of pixels filled by a single task.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_SIZE: usize = 1 << 16;
+
+/// Fill the whole buffer with `color`.
+///
+/// Big buffers are filled in parallel if the `rayon` feature is enabled.
+#[inline]
+pub(crate) fn fill_buffer(buffer: &mut [RGBA8], color: RGBA8) {
+    #[cfg(feature = "rayon")]
+    if buffer.len() >= PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+
+        buffer
+            .par_chunks_mut(PARALLEL_CHUNK_SIZE)
+            .for_each(|chunk| chunk.fill(color));
+        return;
+    }
+
+    buffer.fill(color);
+}
+
+/// Copy `src` to the buffer (they must be the same size).
+///
+/// Big buffers are copied in parallel if the `rayon` feature is enabled.
+#[inline]
+pub(crate) fn copy_buffer(buffer: &mut [RGBA8], src: &[RGBA8]) {
+    #[cfg(feature = "rayon")]
+    if buffer.len() >= PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+
+        buffer
+            .par_chunks_mut(PARALLEL_CHUNK_SIZE)
+            .zip(src.par_chunks(PARALLEL_CHUNK_SIZE))
+            .for_each(|(chunk, src)| chunk.copy_from_slice(src));
+        return;
+    }
+
+    buffer.copy_from_slice(src);
+}
+
+/// Index of a pixel in the buffer or `None` if it's outside the buffer.
+#[inline]
+pub(crate) fn pixel_index<T: DrawTarget + ?Sized>(target: &T, x: i32, y: i32) -> Option<usize> {
+    let (width, height) = (target.buffer_width(), target.buffer_height());
+
+    if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+        None
+    } else {
+        Some(y as usize * width as usize + x as usize)
+    }
+}
+
+/// The area that can be drawn to as `(left, top, right, bottom)` (`right` and `bottom` are exclusive).
+///
+/// This is the clip rectangle intersected with the buffer.
+pub(crate) fn clip_bounds<T: DrawTarget + ?Sized>(target: &T) -> (i64, i64, i64, i64) {
+    let (buf_width, buf_height) = (target.buffer_width() as i64, target.buffer_height() as i64);
+
+    match target.get_clip() {
+        Some((x, y, width, height)) => {
+            let (x, y) = (x as i64, y as i64);
+
+            (
+                x.clamp(0, buf_width),
+                y.clamp(0, buf_height),
+                (x + width as i64).clamp(0, buf_width),
+                (y + height as i64).clamp(0, buf_height),
+            )
+        }
+        None => (0, 0, buf_width, buf_height),
+    }
+}
+
+/// Clip a rectangle to the drawable area.
+///
+/// Returns the position of the visible part in the buffer, its offset inside the rectangle and its size
+/// or `None` if the rectangle is completely clipped.
+pub(crate) fn clip_rect<T: DrawTarget + ?Sized>(
+    target: &T,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(Point, Point, Size)> {
+    clip_rect_to(clip_bounds(target), x, y, width, height)
+}
+
+/// Like [`clip_rect()`], but with the bounds from [`clip_bounds()`] already computed.
+fn clip_rect_to(
+    (clip_left, clip_top, clip_right, clip_bottom): (i64, i64, i64, i64),
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(Point, Point, Size)> {
+    let (x, y) = (x as i64, y as i64);
+
+    let left = x.max(clip_left);
+    let top = y.max(clip_top);
+    let right = (x + width as i64).min(clip_right);
+    let bottom = (y + height as i64).min(clip_bottom);
+
+    if left >= right || top >= bottom {
+        return None;
+    }
+
+    Some((
+        simple_blit::point(left as u32, top as u32),
+        simple_blit::point((left - x) as u32, (top - y) as u32),
+        simple_blit::size((right - left) as u32, (bottom - top) as u32),
+    ))
+}
+
+/// Like [`pixel_index()`], but also returns `None` for pixels outside the clip rectangle.
+#[inline]
+pub(crate) fn clipped_pixel_index<T: DrawTarget + ?Sized>(
+    target: &T,
+    x: i32,
+    y: i32,
+) -> Option<usize> {
+    let (left, top, right, bottom) = clip_bounds(target);
+    let (x, y) = (x as i64, y as i64);
+
+    if x < left || y < top || x >= right || y >= bottom {
+        None
+    } else {
+        Some(y as usize * target.buffer_width() as usize + x as usize)
+    }
+}
+
+/// Fill a range of the buffer according to the current blend mode.
+#[inline]
+pub(crate) fn fill_span<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    range: std::ops::Range<usize>,
+    color: RGBA8,
+) {
+    match target.get_blend_mode() {
+        BlendMode::Replace => target.get_mut_draw_buffer()[range].fill(color),
+        mode => {
+            for pix in &mut target.get_mut_draw_buffer()[range] {
+                blend_into(pix, color, mode);
+            }
+        }
+    }
+}
+
+/// Draw a horizontal span from (x0, y) to (x1, y) (both ends inclusive), clipped to the drawable area.
+pub(crate) fn draw_hline<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    x0: i32,
+    x1: i32,
+    y: i32,
+    color: RGBA8,
+) {
+    let (left, top, right, bottom) = clip_bounds(target);
+
+    if (y as i64) < top || y as i64 >= bottom {
+        return;
+    }
+
+    let x0 = (x0 as i64).max(left);
+    let x1 = (x1 as i64).min(right - 1);
+
+    if x0 > x1 {
+        return;
+    }
+
+    let row = y as usize * target.buffer_width() as usize;
+    fill_span(target, row + x0 as usize..row + x1 as usize + 1, color);
+}
+
+/// Blit `src` to the buffer at `dest_pos` according to the current blend mode, skipping `key` pixels if provided.
+pub(crate) fn blit_blended<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    dest_pos: Point,
+    src: impl Surface<RGBA8>,
+    transforms: &[simple_blit::Transform],
+    key: Option<RGBA8>,
+) {
+    let mode = target.get_blend_mode();
+    blit_with_mode(target, dest_pos, src, transforms, key, mode);
+}
+
+/// Like [`blit_blended()`], but with the given blend mode instead of the current one.
+pub(crate) fn blit_with_mode<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    dest_pos: Point,
+    src: impl Surface<RGBA8>,
+    transforms: &[simple_blit::Transform],
+    key: Option<RGBA8>,
+    mode: BlendMode,
+) {
+    let size = src.surface_size();
+    let buf_size = simple_blit::size(target.buffer_width(), target.buffer_height());
+
+    if let Some(mut dest) = GenericSurface::new(target.get_mut_draw_buffer(), buf_size) {
+        simple_blit::blit_with(
+            dest.sub_surface_mut(dest_pos, size),
+            src,
+            transforms,
+            |dest, &src, _| {
+                if key != Some(src) {
+                    blend_into(dest, src, mode);
+                }
+            },
+        );
+    }
+}
+
+/// Fill a convex polygon with corners at arbitrary (not pixel-aligned) positions.
+///
+/// A pixel is filled if its center is inside the polygon, centers exactly on the bottom or right edges aren't.
+fn fill_convex<T: DrawTarget + ?Sized>(target: &mut T, points: &[(f64, f64)], color: RGBA8) {
+    let (_, clip_top, _, clip_bottom) = clip_bounds(target);
+
+    let top = points.iter().map(|&(_, y)| y).fold(f64::INFINITY, f64::min);
+    let bottom = points
+        .iter()
+        .map(|&(_, y)| y)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    // rows with their centers inside
+    let first_row = ((top - 0.5).ceil() as i64).max(clip_top);
+    let last_row = ((bottom - 0.5).ceil() as i64).min(clip_bottom);
+
+    for y in first_row..last_row {
+        let center_y = y as f64 + 0.5;
+        let (mut left, mut right) = (f64::INFINITY, f64::NEG_INFINITY);
+
+        for (i, &(ax, ay)) in points.iter().enumerate() {
+            let (bx, by) = points[(i + 1) % points.len()];
+
+            if (ay <= center_y && center_y < by) || (by <= center_y && center_y < ay) {
+                let x = ax + (bx - ax) * (center_y - ay) / (by - ay);
+                left = left.min(x);
+                right = right.max(x);
+            }
+        }
+
+        if left < right {
+            // pixels with their centers inside
+            let start = (left - 0.5).ceil() as i64;
+            let end = (right - 0.5).ceil() as i64 - 1;
+
+            draw_hline(
+                target,
+                start.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+                end.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+                y as i32,
+                color,
+            );
+        }
+    }
+}
+
+/// Call `plot` with every point of a circle outline (relative to the center) exactly once.
+fn circle_points(radius: u32, mut plot: impl FnMut(i32, i32)) {
+    if radius == 0 {
+        plot(0, 0);
+        return;
+    }
+
+    // midpoint circle algorithm
+    let (mut x, mut y) = (radius as i32, 0);
+    let mut decision = 1 - x;
+
+    while y <= x {
+        // points on the axes and the diagonals are shared by two octants
+        plot(x, y);
+        plot(-x, -y);
+
+        if y != 0 {
+            plot(x, -y);
+            plot(-x, y);
+        }
+
+        if x != y {
+            plot(y, x);
+            plot(-y, -x);
+
+            if y != 0 {
+                plot(-y, x);
+                plot(y, -x);
+            }
+        }
+
+        if decision < 0 {
+            decision += 2 * y + 3;
+        } else {
+            decision += 2 * (y - x) + 5;
+            x -= 1;
+        }
+
+        y += 1;
+    }
+}
+
+/// Box blur the whole buffer with a window `radius * 2 + 1` pixels wide, using `scratch` for the intermediate result.
+///
+/// Pixels beyond the edges are treated as copies of the edge pixels.
+pub(crate) fn box_blur(
+    buffer: &mut [RGBA8],
+    scratch: &mut Vec<RGBA8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+) {
+    if radius == 0 || buffer.is_empty() {
+        return;
+    }
+
+    let (width, height) = (width as usize, height as usize);
+
+    scratch.clear();
+    scratch.resize(buffer.len(), RGBA8::default());
+
+    // rows, then columns
+    box_blur_pass(buffer, scratch, height, width, width, 1, radius);
+    box_blur_pass(scratch, buffer, width, height, 1, width, radius);
+}
+
+/// Box blur `lines` lines of `src` into `dest`.
+///
+/// Line `i` starts at `i * line_step` and its pixels are `pixel_step` apart.
+fn box_blur_pass(
+    src: &[RGBA8],
+    dest: &mut [RGBA8],
+    lines: usize,
+    line_len: usize,
+    line_step: usize,
+    pixel_step: usize,
+    radius: u32,
+) {
+    let radius = radius as isize;
+    let window = radius as u32 * 2 + 1;
+
+    for line in 0..lines {
+        let start = line * line_step;
+        let pixel = |i: isize| src[start + i.clamp(0, line_len as isize - 1) as usize * pixel_step];
+
+        // running sums of the channels in the window
+        let mut sum = [0u32; 4];
+        let add = |sum: &mut [u32; 4], pix: RGBA8, sign: i32| {
+            for (sum, c) in sum.iter_mut().zip([pix.r, pix.g, pix.b, pix.a]) {
+                *sum = sum.wrapping_add_signed(c as i32 * sign);
+            }
+        };
+
+        (-radius..=radius).for_each(|i| add(&mut sum, pixel(i), 1));
+
+        for i in 0..line_len as isize {
+            let avg = |sum: u32| ((sum + window / 2) / window) as u8;
+            dest[start + i as usize * pixel_step] =
+                RGBA8::new(avg(sum[0]), avg(sum[1]), avg(sum[2]), avg(sum[3]));
+
+            add(&mut sum, pixel(i - radius), -1);
+            add(&mut sum, pixel(i + radius + 1), 1);
+        }
+    }
+}
+
+/// Call `plot` with every point of the bottom right quadrant of an ellipse outline (relative to the center) exactly once.
+fn ellipse_quadrant(rx: u32, ry: u32, mut plot: impl FnMut(i32, i32)) {
+    if ry == 0 {
+        (0..=rx as i32).for_each(|x| plot(x, 0));
+        return;
+    }
+
+    // midpoint ellipse algorithm, the decision variables are multiplied by 4 to stay in integers
+    let (rx2, ry2) = (rx as i64 * rx as i64, ry as i64 * ry as i64);
+    let (mut x, mut y) = (0i64, ry as i64);
+    let (mut dx, mut dy) = (0, 2 * rx2 * y);
+
+    // the region where the slope is less than 1
+    let mut decision = 4 * ry2 - 4 * rx2 * ry as i64 + rx2;
+
+    while dx < dy {
+        plot(x as i32, y as i32);
+
+        x += 1;
+        dx += 2 * ry2;
+
+        if decision < 0 {
+            decision += 4 * (dx + ry2);
+        } else {
+            y -= 1;
+            dy -= 2 * rx2;
+            decision += 4 * (dx - dy + ry2);
+        }
+    }
+
+    // the rest
+    let mut decision = ry2 * (2 * x + 1).pow(2) + 4 * rx2 * (y - 1).pow(2) - 4 * rx2 * ry2;
+
+    while y >= 0 {
+        plot(x as i32, y as i32);
+
+        y -= 1;
+        dy -= 2 * rx2;
+
+        if decision > 0 {
+            decision += 4 * (rx2 - dy);
+        } else {
+            x += 1;
+            dx += 2 * ry2;
+            decision += 4 * (dx - dy + rx2);
+        }
+    }
+}
+
+/// Something that can be drawn on: the [`Context`](crate::Context)'s framebuffer or a [`Canvas`].
+///
+/// All the drawing functions are provided by this trait, so the same code can draw to either of them.
+pub trait DrawTarget {
+    /// Buffer width (in pixels).
+    fn buffer_width(&self) -> u32;
+
+    /// Buffer height (in pixels).
+    fn buffer_height(&self) -> u32;
+
+    /// Returns the buffer's contents (row-major order).
+    fn get_draw_buffer(&self) -> &[RGBA8];
+
+    /// Returns the buffer's contents (row-major order).
+    ///
+    /// Can be used for drawing.
+    fn get_mut_draw_buffer(&mut self) -> &mut [RGBA8];
+
+    /// Returns the clip rectangle `(x, y, width, height)` or `None` if the whole buffer can be drawn to.
+    ///
+    /// All drawing functions skip the pixels outside the clip rectangle.
+    #[inline]
+    fn get_clip(&self) -> Option<(i32, i32, u32, u32)> {
+        None
+    }
+
+    /// Returns how drawn pixels are combined with the pixels already in the buffer.
+    #[inline]
+    fn get_blend_mode(&self) -> BlendMode {
+        BlendMode::Replace
+    }
+
+    /// Draw a pixels at (x, y).
+    ///
+    /// Does nothing if the position is outside the screen.
+    #[inline]
+    fn draw_pixel(&mut self, x: i32, y: i32, color: RGBA8) {
+        if let Some(idx) = clipped_pixel_index(self, x, y) {
+            let mode = self.get_blend_mode();
+            blend_into(&mut self.get_mut_draw_buffer()[idx], color, mode);
+        }
+    }
+
+    /// Draw a pixel at (x, y), compositing it over the existing pixel using its alpha channel.
+    ///
+    /// Always uses [`BlendMode::AlphaBlend`] regardless of the current blend mode.
+    /// Does nothing if the position is outside the screen.
+    #[inline]
+    fn draw_pixel_blended(&mut self, x: i32, y: i32, color: RGBA8) {
+        if let Some(idx) = clipped_pixel_index(self, x, y) {
+            let pix = &mut self.get_mut_draw_buffer()[idx];
+            *pix = blend_over(*pix, color);
+        }
+    }
+
+    /// Get the color of a pixel at (x, y).
+    ///
+    /// Returns `None` if the position is outside the screen.
+    #[inline]
+    fn get_pixel(&self, x: i32, y: i32) -> Option<RGBA8> {
+        pixel_index(self, x, y).map(|idx| self.get_draw_buffer()[idx])
+    }
+
+    /// Draw a straight line from (x0, y0) to (x1, y1) (both ends inclusive).
+    ///
+    /// Does not panic if a part of the line isn't on screen, just draws the part that is.
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: RGBA8) {
+        // Bresenham's line algorithm, generalized for all octants.
+        // The math is done in `i64` so that the distance between any two points fits.
+        let (clip_left, clip_top, clip_right, clip_bottom) = clip_bounds(self);
+
+        if clip_left >= clip_right || clip_top >= clip_bottom {
+            return;
+        }
+
+        let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+
+        // the coordinate along the major axis changes on every step,
+        // so only the steps where it is inside the clip rectangle can draw anything
+        let x_major = dx >= -dy;
+        let (start, length, step, clip_start, clip_end) = if x_major {
+            (x0, dx, step_x, clip_left, clip_right)
+        } else {
+            (y0, -dy, step_y, clip_top, clip_bottom)
+        };
+        let (first, last) = if step > 0 {
+            (clip_start - start, clip_end - 1 - start)
+        } else {
+            (start - (clip_end - 1), start - clip_start)
+        };
+        let (first, last) = (first.max(0), last.min(length));
+
+        if first > last {
+            return;
+        }
+
+        // skip ahead to the first visible step:
+        // after `first` major steps the line has made this many minor steps
+        let minor_steps = if length == 0 {
+            0
+        } else {
+            let minor_length = if x_major { -dy } else { dx } as i128;
+            ((length as i128 + 2 * first as i128 * minor_length) / (2 * length as i128)) as i64
+        };
+        let (steps_x, steps_y) = if x_major {
+            (first, minor_steps)
+        } else {
+            (minor_steps, first)
+        };
+
+        let (mut x, mut y) = (x0 + steps_x * step_x, y0 + steps_y * step_y);
+        let mut err =
+            (dx as i128 + dy as i128 + steps_x as i128 * dy as i128 + steps_y as i128 * dx as i128)
+                as i64;
+
+        for i in first..=last {
+            self.draw_pixel(x as i32, y as i32, color);
+
+            if i == last {
+                break;
+            }
+
+            let err2 = 2 * err;
+
+            if err2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+
+            if err2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draw a line `thickness` pixels thick from (x0, y0) to (x1, y1).
+    ///
+    /// The line has flat (butt) ends that cover the end points, it doesn't extend past them.
+    /// A `thickness` of 0 or 1 draws the same as [`draw_line`](DrawTarget::draw_line).
+    /// Does not panic if a part of the line isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(8, 8, color::BLACK);
+    /// canvas.draw_line_thick(1, 4, 6, 4, 3, color::WHITE);
+    ///
+    /// for y in 0..8 {
+    ///     for x in 0..8 {
+    ///         let inside = (1..=6).contains(&x) && (3..=5).contains(&y);
+    ///         assert_eq!(canvas.get_pixel(x, y) == Some(color::WHITE), inside);
+    ///     }
+    /// }
+    /// ```
+    fn draw_line_thick(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        thickness: u32,
+        color: RGBA8,
+    ) {
+        if thickness <= 1 {
+            self.draw_line(x0, y0, x1, y1, color);
+            return;
+        }
+
+        // pixel centers of the end points
+        let (x0, y0) = (x0 as f64 + 0.5, y0 as f64 + 0.5);
+        let (x1, y1) = (x1 as f64 + 0.5, y1 as f64 + 0.5);
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len = dx.hypot(dy);
+        let (dir_x, dir_y) = if len == 0. {
+            (1., 0.)
+        } else {
+            (dx / len, dy / len)
+        };
+
+        // extend both ends by half a pixel to cover the end points, the sides are half the thickness away
+        let (ext_x, ext_y) = (dir_x * 0.5, dir_y * 0.5);
+        let half = thickness as f64 / 2.;
+        let (side_x, side_y) = (-dir_y * half, dir_x * half);
+
+        let (start_x, start_y) = (x0 - ext_x, y0 - ext_y);
+        let (end_x, end_y) = (x1 + ext_x, y1 + ext_y);
+
+        fill_convex(
+            self,
+            &[
+                (start_x + side_x, start_y + side_y),
+                (end_x + side_x, end_y + side_y),
+                (end_x - side_x, end_y - side_y),
+                (start_x - side_x, start_y - side_y),
+            ],
+            color,
+        );
+    }
+
+    /// Draw a circle outline centered at (cx, cy).
+    ///
+    /// Does not panic if a part of the circle isn't on screen, just draws the part that is.
+    fn draw_circle(&mut self, cx: i32, cy: i32, radius: u32, color: RGBA8) {
+        circle_points(radius, |x, y| self.draw_pixel(cx + x, cy + y, color));
+    }
+
+    /// Draw a part of a circle outline centered at (cx, cy) from `start_deg` to `end_deg`.
+    ///
+    /// The angles are in degrees clockwise from the positive x axis (to the right).
+    /// The arc always goes clockwise from `start_deg`, wrapping past 360° if `end_deg` is smaller.
+    /// If the angles are 360° or more apart, the whole circle is drawn.
+    /// Does not panic if a part of the arc isn't on screen, just draws the part that is.
+    fn draw_arc(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        radius: u32,
+        start_deg: f32,
+        end_deg: f32,
+        color: RGBA8,
+    ) {
+        let start = start_deg.rem_euclid(360.);
+        let span = if end_deg - start_deg >= 360. {
+            360.
+        } else {
+            (end_deg - start_deg).rem_euclid(360.)
+        };
+
+        circle_points(radius, |x, y| {
+            let angle = (y as f32).atan2(x as f32).to_degrees();
+
+            if (angle - start).rem_euclid(360.) <= span {
+                self.draw_pixel(cx + x, cy + y, color);
+            }
+        });
+    }
+
+    /// Draw an ellipse outline centered at (cx, cy) with `rx` and `ry` radii.
+    ///
+    /// If one of the radii is zero, a line is drawn.
+    /// Does not panic if a part of the ellipse isn't on screen, just draws the part that is.
+    fn draw_ellipse(&mut self, cx: i32, cy: i32, rx: u32, ry: u32, color: RGBA8) {
+        ellipse_quadrant(rx, ry, |x, y| {
+            // points on the axes are shared by two quadrants
+            self.draw_pixel(cx + x, cy + y, color);
+
+            if x != 0 {
+                self.draw_pixel(cx - x, cy + y, color);
+            }
+
+            if y != 0 {
+                self.draw_pixel(cx + x, cy - y, color);
+
+                if x != 0 {
+                    self.draw_pixel(cx - x, cy - y, color);
+                }
+            }
+        });
+    }
+
+    /// Draw a filled ellipse centered at (cx, cy) with `rx` and `ry` radii.
+    ///
+    /// If one of the radii is zero, a line is drawn.
+    /// Does not panic if a part of the ellipse isn't on screen, just draws the part that is.
+    fn draw_ellipse_filled(&mut self, cx: i32, cy: i32, rx: u32, ry: u32, color: RGBA8) {
+        // the widest span of every row, so that every row is filled exactly once
+        let mut spans = vec![0; ry as usize + 1];
+
+        ellipse_quadrant(rx, ry, |x, y| {
+            spans[y as usize] = spans[y as usize].max(x);
+        });
+
+        for (y, &x) in spans.iter().enumerate() {
+            let y = y as i32;
+            draw_hline(self, cx - x, cx + x, cy + y, color);
+
+            if y != 0 {
+                draw_hline(self, cx - x, cx + x, cy - y, color);
+            }
+        }
+    }
+
+    /// Draw a filled circle centered at (cx, cy).
+    ///
+    /// The circle covers the same pixels as [`draw_circle`](DrawTarget::draw_circle) and everything inside them,
+    /// so the outline is included and the area is close to π(radius + ½)² rather than π·radius².
+    /// Does not panic if a part of the circle isn't on screen, just draws the part that is.
+    fn draw_circle_filled(&mut self, cx: i32, cy: i32, radius: u32, color: RGBA8) {
+        // midpoint circle algorithm, every row is filled exactly once
+        let (mut x, mut y) = (radius as i32, 0);
+        let mut decision = 1 - x;
+
+        while y <= x {
+            draw_hline(self, cx - x, cx + x, cy + y, color);
+
+            if y != 0 {
+                draw_hline(self, cx - x, cx + x, cy - y, color);
+            }
+
+            if decision < 0 {
+                decision += 2 * y + 3;
+            } else {
+                // row `x` is about to be left behind, so its span is final
+                if x != y {
+                    draw_hline(self, cx - y, cx + y, cy + x, color);
+                    draw_hline(self, cx - y, cx + y, cy - x, color);
+                }
+
+                decision += 2 * (y - x) + 5;
+                x -= 1;
+            }
+
+            y += 1;
+        }
+    }
+
+    /// Draw a triangle outline.
+    ///
+    /// Does not panic if a part of the triangle isn't on screen, just draws the part that is.
+    fn draw_triangle(&mut self, p0: (i32, i32), p1: (i32, i32), p2: (i32, i32), color: RGBA8) {
+        self.draw_line(p0.0, p0.1, p1.0, p1.1, color);
+        self.draw_line(p1.0, p1.1, p2.0, p2.1, color);
+        self.draw_line(p2.0, p2.1, p0.0, p0.1, color);
+    }
+
+    /// Draw a filled triangle.
+    ///
+    /// Follows the top-left rule: pixels on the top and left edges are drawn, pixels on the bottom and right ones aren't,
+    /// so triangles sharing an edge don't overlap or leave gaps between them.
+    /// Degenerate (zero-area) triangles aren't drawn.
+    /// Does not panic if a part of the triangle isn't on screen, just draws the part that is.
+    fn draw_triangle_filled(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        color: RGBA8,
+    ) {
+        let mut points = [p0, p1, p2].map(|(x, y)| (x as i64, y as i64));
+        points.sort_unstable_by_key(|&(_, y)| y);
+        let [top, mid, bottom] = points;
+
+        let cross = (mid.0 - top.0) * (bottom.1 - top.1) - (mid.1 - top.1) * (bottom.0 - top.0);
+
+        if cross == 0 {
+            return;
+        }
+
+        // first x on the row that is on or to the right of the edge
+        let edge_x = |(ax, ay): (i64, i64), (bx, by): (i64, i64), y: i64| {
+            let num = ax * (by - ay) + (bx - ax) * (y - ay);
+            let den = by - ay;
+
+            num.div_euclid(den) + (num.rem_euclid(den) != 0) as i64
+        };
+
+        let (_, clip_top, _, clip_bottom) = clip_bounds(self);
+
+        for y in top.1.max(clip_top)..bottom.1.min(clip_bottom) {
+            let long_x = edge_x(top, bottom, y);
+            let short_x = if y < mid.1 {
+                edge_x(top, mid, y)
+            } else {
+                edge_x(mid, bottom, y)
+            };
+
+            // the middle point is to the left of the long edge if `cross` is negative
+            let (start, end) = if cross < 0 {
+                (short_x, long_x)
+            } else {
+                (long_x, short_x)
+            };
+
+            if start < end {
+                draw_hline(self, start as i32, (end - 1) as i32, y as i32, color);
+            }
+        }
+    }
+
+    /// Draw lines connecting consecutive points.
+    ///
+    /// A single point is drawn as a pixel, an empty slice draws nothing.
+    /// Does not panic if a part of the lines isn't on screen, just draws the part that is.
+    fn draw_polyline(&mut self, points: &[(i32, i32)], color: RGBA8) {
+        if let [(x, y)] = points {
+            self.draw_pixel(*x, *y, color);
+        }
+
+        for pair in points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+            self.draw_line(x0, y0, x1, y1, color);
+        }
+    }
+
+    /// Draw a filled polygon, the last point is connected to the first one.
+    ///
+    /// Uses the even-odd rule, so concave and self-intersecting polygons are supported.
+    /// Follows the top-left rule like [`draw_triangle_filled`](DrawTarget::draw_triangle_filled),
+    /// so polygons sharing an edge don't overlap or leave gaps between them.
+    /// Polygons with less than 3 points aren't drawn.
+    /// Does not panic if a part of the polygon isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let white = RGBA8::new(255, 255, 255, 255);
+    /// let mut canvas = Canvas::new(12, 10, RGBA8::default());
+    ///
+    /// // an arrow pointing to the right with a notch in its tail
+    /// canvas.draw_polygon_filled(&[(0, 2), (6, 2), (6, 0), (11, 5), (6, 10), (6, 8), (0, 8), (3, 5)], white);
+    ///
+    /// assert_eq!(canvas.get_pixel(5, 5), Some(white));
+    /// assert_eq!(canvas.get_pixel(9, 5), Some(white));
+    /// // inside the notch
+    /// assert_eq!(canvas.get_pixel(1, 5), Some(RGBA8::default()));
+    /// // above the shaft
+    /// assert_eq!(canvas.get_pixel(2, 1), Some(RGBA8::default()));
+    /// ```
+    fn draw_polygon_filled(&mut self, points: &[(i32, i32)], color: RGBA8) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let points: Vec<_> = points.iter().map(|&(x, y)| (x as i64, y as i64)).collect();
+        let edges: Vec<_> = (0..points.len())
+            .map(|i| (points[i], points[(i + 1) % points.len()]))
+            // horizontal edges don't cross any row
+            .filter(|((_, ay), (_, by))| ay != by)
+            .map(|(a, b)| if a.1 < b.1 { (a, b) } else { (b, a) })
+            .collect();
+
+        let (_, clip_top, _, clip_bottom) = clip_bounds(self);
+        let top = points.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let bottom = points.iter().map(|&(_, y)| y).max().unwrap_or(0);
+
+        let mut crossings = Vec::new();
+
+        for y in top.max(clip_top)..bottom.min(clip_bottom) {
+            crossings.clear();
+
+            // an edge covers the rows from its top point up to (but not including) its bottom one,
+            // so a vertex shared by two edges is only counted once
+            crossings.extend(
+                edges
+                    .iter()
+                    .filter(|((_, ay), (_, by))| (*ay..*by).contains(&y))
+                    .map(|&((ax, ay), (bx, by))| {
+                        // first x on the row that is on or to the right of the edge
+                        let num = ax * (by - ay) + (bx - ax) * (y - ay);
+                        let den = by - ay;
+
+                        num.div_euclid(den) + (num.rem_euclid(den) != 0) as i64
+                    }),
+            );
+
+            crossings.sort_unstable();
+
+            for span in crossings.chunks_exact(2) {
+                if span[0] < span[1] {
+                    draw_hline(self, span[0] as i32, (span[1] - 1) as i32, y as i32, color);
+                }
+            }
+        }
+    }
+
+    /// Draw a colored rectangle.
+    ///
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: RGBA8) {
+        if let Some((dest_pos, _, size)) = clip_rect(self, x, y, width, height) {
+            let buf_width = self.buffer_width() as usize;
+
+            // full-width rows are contiguous, so they can be filled at once
+            if size.x as usize == buf_width {
+                let start = dest_pos.y as usize * buf_width;
+                fill_span(self, start..start + size.y as usize * buf_width, color);
+                return;
+            }
+
+            for iy in 0..size.y {
+                let start = (dest_pos.y + iy) as usize * buf_width + dest_pos.x as usize;
+                fill_span(self, start..start + size.x as usize, color);
+            }
+        }
+    }
+
+    /// Draw a rectangle outline with the border `thickness` pixels thick (drawn inside the rectangle).
+    ///
+    /// If the border is too thick to leave any space inside, the whole rectangle is filled.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_rect_outline(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        thickness: u32,
+        color: RGBA8,
+    ) {
+        if thickness == 0 {
+            return;
+        }
+
+        if thickness.saturating_mul(2) >= width.min(height) {
+            self.draw_rect(x, y, width, height, color);
+            return;
+        }
+
+        let inner_height = height - thickness * 2;
+
+        // top, bottom, left, right
+        self.draw_rect(x, y, width, thickness, color);
+        self.draw_rect(x, y + (height - thickness) as i32, width, thickness, color);
+        self.draw_rect(x, y + thickness as i32, thickness, inner_height, color);
+        self.draw_rect(
+            x + (width - thickness) as i32,
+            y + thickness as i32,
+            thickness,
+            inner_height,
+            color,
+        );
+    }
+
+    /// Draw a rectangle filled with `fill` with a `border` colored border `border_thickness` pixels thick
+    /// (drawn inside the rectangle).
+    ///
+    /// The fill and the border don't overlap. If the border is too thick to leave any space inside,
+    /// the whole rectangle is filled with `border`.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(8, 8, color::BLACK);
+    /// canvas.draw_rect_bordered(1, 1, 6, 5, color::BLUE, color::WHITE, 1);
+    ///
+    /// assert_eq!(canvas.get_pixel(1, 1), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(6, 3), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(3, 5), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(2, 2), Some(color::BLUE));
+    /// assert_eq!(canvas.get_pixel(5, 4), Some(color::BLUE));
+    /// assert_eq!(canvas.get_pixel(7, 6), Some(color::BLACK));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn draw_rect_bordered(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        fill: RGBA8,
+        border: RGBA8,
+        border_thickness: u32,
+    ) {
+        if border_thickness.saturating_mul(2) < width.min(height) {
+            self.draw_rect(
+                x + border_thickness as i32,
+                y + border_thickness as i32,
+                width - border_thickness * 2,
+                height - border_thickness * 2,
+                fill,
+            );
+        }
+
+        self.draw_rect_outline(x, y, width, height, border_thickness, border);
+    }
+
+    /// Draw a rectangle outline with quarter-circle corners of the given `radius`.
+    ///
+    /// The radius is clamped to half of the smaller side, with zero radius it's the same as a 1-pixel thick
+    /// [`draw_rect_outline`](DrawTarget::draw_rect_outline).
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let white = RGBA8::new(255, 255, 255, 255);
+    /// let mut round = Canvas::new(8, 8, RGBA8::default());
+    /// let mut square = round.clone();
+    ///
+    /// round.draw_round_rect(1, 2, 6, 5, 0, white);
+    /// square.draw_rect_outline(1, 2, 6, 5, 1, white);
+    /// assert_eq!(round.pixels(), square.pixels());
+    ///
+    /// round.draw_round_rect_filled(1, 2, 6, 5, 0, white);
+    /// square.draw_rect(1, 2, 6, 5, white);
+    /// assert_eq!(round.pixels(), square.pixels());
+    /// ```
+    fn draw_round_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        radius: u32,
+        color: RGBA8,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let radius = radius.min(width / 2).min(height / 2);
+
+        // centers of the corners
+        let (left, top) = (x + radius as i32, y + radius as i32);
+        let right = x + (width - 1 - radius) as i32;
+        let bottom = y + (height - 1 - radius) as i32;
+
+        // top and bottom
+        let edge_width = width - radius * 2;
+        self.draw_rect(left, y, edge_width, 1, color);
+
+        if height > 1 {
+            self.draw_rect(left, y + height as i32 - 1, edge_width, 1, color);
+        }
+
+        // left and right, without the corner pixels if there are no rounded corners to take them
+        let (side_y, side_height) = if radius == 0 {
+            (y + 1, height.saturating_sub(2))
+        } else {
+            (top, height - radius * 2)
+        };
+
+        self.draw_rect(x, side_y, 1, side_height, color);
+
+        if width > 1 {
+            self.draw_rect(x + width as i32 - 1, side_y, 1, side_height, color);
+        }
+
+        circle_points(radius, |dx, dy| {
+            // points on the axes are already drawn by the edges
+            if dx != 0 && dy != 0 {
+                let px = if dx < 0 { left + dx } else { right + dx };
+                let py = if dy < 0 { top + dy } else { bottom + dy };
+
+                self.draw_pixel(px, py, color);
+            }
+        });
+    }
+
+    /// Draw a filled rectangle with quarter-circle corners of the given `radius`.
+    ///
+    /// The radius is clamped to half of the smaller side, with zero radius it's the same as
+    /// [`draw_rect`](DrawTarget::draw_rect).
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_round_rect_filled(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        radius: u32,
+        color: RGBA8,
+    ) {
+        let radius = radius.min(width / 2).min(height / 2);
+
+        if radius == 0 {
+            self.draw_rect(x, y, width, height, color);
+            return;
+        }
+
+        // centers of the corners
+        let (left, top) = (x + radius as i32, y + radius as i32);
+        let right = x + (width - 1 - radius) as i32;
+        let bottom = y + (height - 1 - radius) as i32;
+
+        // how far the corners reach out horizontally on every row, so that every row is filled exactly once
+        let mut reach = vec![0; radius as usize + 1];
+
+        circle_points(radius, |dx, dy| {
+            let dy = dy.unsigned_abs() as usize;
+            reach[dy] = reach[dy].max(dx.abs());
+        });
+
+        self.draw_rect(x, top, width, height - radius * 2, color);
+
+        for (dy, &dx) in reach.iter().enumerate().skip(1) {
+            draw_hline(self, left - dx, right + dx, top - dy as i32, color);
+            draw_hline(self, left - dx, right + dx, bottom + dy as i32, color);
+        }
+    }
+
+    /// Fill a rectangle with a horizontal gradient from `left` to `right`.
+    ///
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_gradient_h(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        left: RGBA8,
+        right: RGBA8,
+    ) {
+        if let Some((dest_pos, src_pos, size)) = clip_rect(self, x, y, width, height) {
+            let mode = self.get_blend_mode();
+            let buf_width = self.buffer_width() as usize;
+            let buffer = self.get_mut_draw_buffer();
+
+            for ix in 0..size.x {
+                let color = lerp_color(left, right, src_pos.x + ix, width - 1);
+
+                for iy in 0..size.y {
+                    let idx = (dest_pos.y + iy) as usize * buf_width + (dest_pos.x + ix) as usize;
+                    blend_into(&mut buffer[idx], color, mode);
+                }
+            }
+        }
+    }
+
+    /// Fill a rectangle with a vertical gradient from `top` to `bottom`.
+    ///
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_gradient_v(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        top: RGBA8,
+        bottom: RGBA8,
+    ) {
+        if let Some((dest_pos, src_pos, size)) = clip_rect(self, x, y, width, height) {
+            for iy in 0..size.y {
+                let color = lerp_color(top, bottom, src_pos.y + iy, height - 1);
+                let start =
+                    (dest_pos.y + iy) as usize * self.buffer_width() as usize + dest_pos.x as usize;
+                fill_span(self, start..start + size.x as usize, color);
+            }
+        }
+    }
+
+    /// Fill a rectangle with a checkerboard of `cell`×`cell` squares, starting with `color_a` at the top left corner.
+    ///
+    /// A `cell` of 0 is treated as 1.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(8, 8, color::BLACK);
+    /// canvas.draw_checkerboard(1, 1, 6, 6, 2, color::WHITE, color::GRAY);
+    ///
+    /// assert_eq!(canvas.get_pixel(1, 1), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(2, 2), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(3, 2), Some(color::GRAY));
+    /// assert_eq!(canvas.get_pixel(2, 3), Some(color::GRAY));
+    /// assert_eq!(canvas.get_pixel(3, 3), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(6, 6), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(7, 7), Some(color::BLACK));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn draw_checkerboard(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        cell: u32,
+        color_a: RGBA8,
+        color_b: RGBA8,
+    ) {
+        let cell = cell.max(1);
+
+        if let Some((dest_pos, src_pos, size)) = clip_rect(self, x, y, width, height) {
+            let buf_width = self.buffer_width() as usize;
+
+            for iy in 0..size.y {
+                let row = (dest_pos.y + iy) as usize * buf_width;
+                let odd_row = (src_pos.y + iy) / cell % 2 == 1;
+
+                // fill cell by cell, the first and the last ones may be cut off
+                let mut ix = 0;
+
+                while ix < size.x {
+                    let cell_x = (src_pos.x + ix) / cell;
+                    let cell_end = ((cell_x + 1) * cell - src_pos.x).min(size.x);
+                    let color = if (cell_x % 2 == 1) == odd_row {
+                        color_a
+                    } else {
+                        color_b
+                    };
+
+                    let start = row + (dest_pos.x + ix) as usize;
+                    fill_span(self, start..start + (cell_end - ix) as usize, color);
+
+                    ix = cell_end;
+                }
+            }
+        }
+    }
+
+    /// Fill a rectangle with opaque grayscale [value noise](crate::noise::value_noise_2d).
+    ///
+    /// The noise is sampled relative to the top left corner of the rectangle, `scale` is the size of one noise cell in pixels
+    /// (non-positive values are treated as 1).
+    /// The same arguments always produce the same pixels.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget};
+    /// let mut first = Canvas::new(16, 16, color::BLACK);
+    /// let mut second = Canvas::new(16, 16, color::BLACK);
+    ///
+    /// first.fill_noise(0, 0, 16, 16, 4., 7);
+    /// second.fill_noise(0, 0, 16, 16, 4., 7);
+    /// assert_eq!(first, second);
+    ///
+    /// let pix = first.get_pixel(5, 9).unwrap();
+    /// assert!(pix.r == pix.g && pix.g == pix.b && pix.a == 255);
+    /// ```
+    fn fill_noise(&mut self, x: i32, y: i32, width: u32, height: u32, scale: f32, seed: u64) {
+        let scale = if scale > 0. { scale } else { 1. };
+
+        if let Some((dest_pos, src_pos, size)) = clip_rect(self, x, y, width, height) {
+            let buf_width = self.buffer_width() as usize;
+            let mode = self.get_blend_mode();
+
+            for iy in 0..size.y {
+                let row = (dest_pos.y + iy) as usize * buf_width + dest_pos.x as usize;
+                let ny = (src_pos.y + iy) as f32 / scale;
+
+                for ix in 0..size.x {
+                    let nx = (src_pos.x + ix) as f32 / scale;
+                    let value = (crate::noise::value_noise_2d(nx, ny, seed) * 256.) as u8;
+
+                    blend_into(
+                        &mut self.get_mut_draw_buffer()[row + ix as usize],
+                        RGBA8::new(value, value, value, 255),
+                        mode,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order).
+    ///
+    /// Does nothing if the length of `pixels` isn't `width * height` (and panics in debug builds),
+    /// use [`try_draw_pixels`](DrawTarget::try_draw_pixels) to handle that.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_pixels(&mut self, x: i32, y: i32, width: u32, height: u32, pixels: &[RGBA8]) {
+        debug_assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "the number of pixels doesn't match the rectangle size"
+        );
+
+        if let (Some(buffer), Some((dest_pos, src_pos, size))) = (
+            simple_blit::GenericSurface::new(pixels, [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            blit_blended(self, dest_pos, buffer.sub_surface(src_pos, size), &[], None);
+        }
+    }
+
+    /// Like [`draw_pixels`](DrawTarget::draw_pixels), but returns an error if the length of `pixels` isn't `width * height`.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawError, DrawTarget};
+    /// let mut canvas = Canvas::new(4, 4, color::BLACK);
+    ///
+    /// assert_eq!(
+    ///     canvas.try_draw_pixels(0, 0, 2, 2, &[color::RED; 3]),
+    ///     Err(DrawError::PixelCountMismatch { expected: 4, actual: 3 })
+    /// );
+    /// assert_eq!(canvas.get_pixel(0, 0), Some(color::BLACK));
+    ///
+    /// assert_eq!(canvas.try_draw_pixels(0, 0, 2, 2, &[color::RED; 4]), Ok(()));
+    /// assert_eq!(canvas.get_pixel(1, 1), Some(color::RED));
+    /// ```
+    #[inline]
+    fn try_draw_pixels(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+    ) -> Result<(), DrawError> {
+        let expected = width as usize * height as usize;
+
+        if pixels.len() != expected {
+            return Err(DrawError::PixelCountMismatch {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        self.draw_pixels(x, y, width, height, pixels);
+        Ok(())
+    }
+
+    /// Composite provided pixels (row-major order) over a rectangle with their alpha multiplied by `alpha / 255`.
+    ///
+    /// Can be used to fade a whole sprite in or out.
+    /// Always uses [`BlendMode::AlphaBlend`] regardless of the current blend mode,
+    /// so an `alpha` of 255 draws the same as [`draw_pixels`](DrawTarget::draw_pixels) with that mode and 0 draws nothing.
+    /// Does nothing if `pixels` has the wrong length.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(2, 2, RGBA8::new(0, 0, 0, 255));
+    ///
+    /// canvas.draw_pixels_alpha(0, 0, 2, 2, &[RGBA8::new(255, 255, 255, 255); 4], 128);
+    ///
+    /// assert_eq!(canvas.get_pixel(1, 1), Some(RGBA8::new(128, 128, 128, 255)));
+    /// ```
+    fn draw_pixels_alpha(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        alpha: u8,
+    ) {
+        if alpha == 0 {
+            return;
+        }
+
+        let buf_size = simple_blit::size(self.buffer_width(), self.buffer_height());
+
+        if let (Some(src), Some((dest_pos, src_pos, size))) = (
+            GenericSurface::new(pixels, [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            if let Some(mut dest) = GenericSurface::new(self.get_mut_draw_buffer(), buf_size) {
+                simple_blit::blit_with(
+                    dest.sub_surface_mut(dest_pos, size),
+                    src.sub_surface(src_pos, size),
+                    &[],
+                    |dest, &src, _| {
+                        let a = (src.a as u32 * alpha as u32 + 127) / 255;
+                        *dest = blend_over(*dest, RGBA8 { a: a as u8, ..src });
+                    },
+                );
+            }
+        }
+    }
+
+    /// Fill many rectangles with provided pixels, given as `(x, y, width, height, pixels)`.
+    ///
+    /// Does the same as calling [`draw_pixels`](DrawTarget::draw_pixels) for every item in order,
+    /// but the buffer, the clipping and the blend mode are only looked up once.
+    /// Items with the wrong number of pixels are skipped.
+    /// Does not panic if a part of a rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let red = [RGBA8::new(255, 0, 0, 255); 4];
+    /// let blue = [RGBA8::new(0, 0, 255, 255); 9];
+    /// let items: [(i32, i32, u32, u32, &[RGBA8]); 3] =
+    ///     [(1, 1, 2, 2, &red), (2, 2, 3, 3, &blue), (-1, 4, 2, 2, &red)];
+    ///
+    /// let mut batched = Canvas::new(5, 5, RGBA8::default());
+    /// let mut sequential = batched.clone();
+    ///
+    /// batched.draw_pixels_batch(&items);
+    ///
+    /// for (x, y, width, height, pixels) in items {
+    ///     sequential.draw_pixels(x, y, width, height, pixels);
+    /// }
+    ///
+    /// assert_eq!(batched, sequential);
+    /// ```
+    fn draw_pixels_batch(&mut self, items: &[(i32, i32, u32, u32, &[RGBA8])]) {
+        let bounds = clip_bounds(self);
+        let mode = self.get_blend_mode();
+        let buf_size = simple_blit::size(self.buffer_width(), self.buffer_height());
+
+        let Some(mut dest) = GenericSurface::new(self.get_mut_draw_buffer(), buf_size) else {
+            return;
+        };
+
+        for &(x, y, width, height, pixels) in items {
+            if let (Some(src), Some((dest_pos, src_pos, size))) = (
+                GenericSurface::new(pixels, [width, height].into()),
+                clip_rect_to(bounds, x, y, width, height),
+            ) {
+                simple_blit::blit_with(
+                    dest.sub_surface_mut(dest_pos, size),
+                    src.sub_surface(src_pos, size),
+                    &[],
+                    |dest, &src, _| blend_into(dest, src, mode),
+                );
+            }
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order) with rows `stride` pixels apart.
+    ///
+    /// Can be used to draw a part of a bigger image (e.g. a sprite sheet) without copying it out first:
+    /// pass the slice starting at the top left pixel of the part and the image width as `stride`.
+    /// Does nothing if `stride` is less than `width` or `pixels` is too short.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let sheet: Vec<RGBA8> = (0..16).map(|i| RGBA8::new(i, 0, 0, 255)).collect();
+    /// let mut canvas = Canvas::new(2, 2, RGBA8::default());
+    ///
+    /// // the 2×2 part of the 4×4 sheet at (1, 1)
+    /// canvas.draw_pixels_strided(0, 0, 2, 2, &sheet[5..], 4);
+    ///
+    /// let reds: Vec<u8> = canvas.pixels().iter().map(|pix| pix.r).collect();
+    /// assert_eq!(reds, [5, 6, 9, 10]);
+    /// ```
+    fn draw_pixels_strided(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        stride: u32,
+    ) {
+        if stride < width || height == 0 {
+            return;
+        }
+
+        // the last row doesn't need the padding
+        if pixels.len() < (height - 1) as usize * stride as usize + width as usize {
+            return;
+        }
+
+        if let Some((dest_pos, src_pos, size)) = clip_rect(self, x, y, width, height) {
+            for iy in 0..size.y {
+                let start = (src_pos.y + iy) as usize * stride as usize + src_pos.x as usize;
+
+                if let Some(row) = simple_blit::GenericSurface::new(
+                    &pixels[start..start + size.x as usize],
+                    simple_blit::size(size.x, 1),
+                ) {
+                    blit_blended(
+                        self,
+                        simple_blit::point(dest_pos.x, dest_pos.y + iy),
+                        row,
+                        &[],
+                        None,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order), skipping the pixels equal to `key`.
+    ///
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_pixels_keyed(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        key: RGBA8,
+    ) {
+        if let (Some(buffer), Some((dest_pos, src_pos, size))) = (
+            simple_blit::GenericSurface::new(pixels, [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            blit_blended(
+                self,
+                dest_pos,
+                buffer.sub_surface(src_pos, size),
+                &[],
+                Some(key),
+            );
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order), skipping the pixels where `mask` (row-major order) is `false`.
+    ///
+    /// Draws nothing if the length of `pixels` or `mask` isn't `width * height`.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(8, 8, color::BLACK);
+    /// let sprite = [color::RED; 36];
+    ///
+    /// // a circle through the middles of the sprite's edges
+    /// let mask: Vec<bool> = (0..36)
+    ///     .map(|idx| {
+    ///         let (dx, dy) = ((idx % 6) as f32 - 2.5, (idx / 6) as f32 - 2.5);
+    ///         dx * dx + dy * dy <= 9.
+    ///     })
+    ///     .collect();
+    ///
+    /// canvas.draw_pixels_masked(1, 1, 6, 6, &sprite, &mask);
+    ///
+    /// assert_eq!(canvas.get_pixel(1, 1), Some(color::BLACK));
+    /// assert_eq!(canvas.get_pixel(6, 1), Some(color::BLACK));
+    /// assert_eq!(canvas.get_pixel(1, 6), Some(color::BLACK));
+    /// assert_eq!(canvas.get_pixel(6, 6), Some(color::BLACK));
+    /// assert_eq!(canvas.get_pixel(3, 3), Some(color::RED));
+    /// assert_eq!(canvas.get_pixel(1, 3), Some(color::RED));
+    /// ```
+    fn draw_pixels_masked(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        mask: &[bool],
+    ) {
+        if mask.len() != width as usize * height as usize {
+            return;
+        }
+
+        if let (Some(buffer), Some((dest_pos, src_pos, size))) = (
+            GenericSurface::new(pixels, [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            let mode = self.get_blend_mode();
+            let buf_size = simple_blit::size(self.buffer_width(), self.buffer_height());
+
+            if let Some(mut dest) = GenericSurface::new(self.get_mut_draw_buffer(), buf_size) {
+                simple_blit::blit_with(
+                    dest.sub_surface_mut(dest_pos, size),
+                    buffer.sub_surface(src_pos, size),
+                    &[],
+                    |dest, &src, pos| {
+                        // `pos` is relative to the visible part of the pixels
+                        let idx = (src_pos.y + pos.y) as usize * width as usize
+                            + (src_pos.x + pos.x) as usize;
+
+                        if mask[idx] {
+                            blend_into(dest, src, mode);
+                        }
+                    },
+                );
+            }
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order), flipping them horizontally and/or vertically.
+    ///
+    /// Flipping both ways is the same as rotating by 180 degrees.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_pixels_flipped(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        if let (Some(buffer), Some((dest_pos, mut src_pos, size))) = (
+            simple_blit::GenericSurface::new(pixels, [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            // the visible part of the flipped rectangle comes from the mirrored part of the source
+            if flip_x {
+                src_pos.x = width - src_pos.x - size.x;
+            }
+
+            if flip_y {
+                src_pos.y = height - src_pos.y - size.y;
+            }
+
+            let transform = match (flip_x, flip_y) {
+                (false, false) => None,
+                (true, false) => Some(simple_blit::Transform::FlipHorizontal),
+                (false, true) => Some(simple_blit::Transform::FlipVertical),
+                (true, true) => Some(simple_blit::Transform::FlipBoth),
+            };
+
+            blit_blended(
+                self,
+                dest_pos,
+                buffer.sub_surface(src_pos, size),
+                transform.as_slice(),
+                None,
+            );
+        }
+    }
+
+    /// Fill a rectangle with provided pixels (row-major order), each scaled up to a `scale_x`×`scale_y` block.
+    ///
+    /// The rectangle is `src_width * scale_x` pixels wide and `src_height * scale_y` pixels high.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_pixels_scaled(
+        &mut self,
+        x: i32,
+        y: i32,
+        src_width: u32,
+        src_height: u32,
+        pixels: &[RGBA8],
+        scale_x: u32,
+        scale_y: u32,
+    ) {
+        if pixels.len() != src_width as usize * src_height as usize {
+            return;
+        }
+
+        let (width, height) = (
+            src_width.saturating_mul(scale_x),
+            src_height.saturating_mul(scale_y),
+        );
+
+        if let Some((dest_pos, offset, size)) = clip_rect(self, x, y, width, height) {
+            let mode = self.get_blend_mode();
+            let buf_width = self.buffer_width() as usize;
+            let buffer = self.get_mut_draw_buffer();
+
+            for iy in 0..size.y {
+                let src_row = ((offset.y + iy) / scale_y) as usize * src_width as usize;
+                let dest_row = (dest_pos.y + iy) as usize * buf_width + dest_pos.x as usize;
+
+                for ix in 0..size.x {
+                    let src = pixels[src_row + ((offset.x + ix) / scale_x) as usize];
+                    blend_into(&mut buffer[dest_row + ix as usize], src, mode);
+                }
+            }
+        }
+    }
+
+    /// Draw provided pixels (row-major order) rotated by `angle_radians` (clockwise) about their center.
+    ///
+    /// The center ends up at (cx, cy), so with no rotation the top left corner is at `(cx - width / 2, cy - height / 2)`.
+    /// Uses nearest-neighbor sampling, fully transparent pixels are skipped.
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_pixels_rotated(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        angle_radians: f32,
+    ) {
+        if pixels.len() != width as usize * height as usize || pixels.is_empty() {
+            return;
+        }
+
+        let (half_width, half_height) = (width as f64 / 2., height as f64 / 2.);
+        // the exact center point, which is between pixels for even sizes
+        let center_x = cx as f64 - (width / 2) as f64 + half_width;
+        let center_y = cy as f64 - (height / 2) as f64 + half_height;
+
+        let (sin, cos) = match (angle_radians as f64).sin_cos() {
+            // snap right angles, rounding errors would make the edges jagged
+            (sin, cos) if sin.abs() < 1e-6 => (0., cos.signum()),
+            (sin, cos) if cos.abs() < 1e-6 => (sin.signum(), 0.),
+            sin_cos => sin_cos,
+        };
+
+        // bounding box of the rotated corners
+        let extent_x = (half_width * cos).abs() + (half_height * sin).abs();
+        let extent_y = (half_width * sin).abs() + (half_height * cos).abs();
+
+        let (clip_left, clip_top, clip_right, clip_bottom) = clip_bounds(self);
+        let left = ((center_x - extent_x).floor() as i64).max(clip_left);
+        let top = ((center_y - extent_y).floor() as i64).max(clip_top);
+        let right = ((center_x + extent_x).ceil() as i64).min(clip_right);
+        let bottom = ((center_y + extent_y).ceil() as i64).min(clip_bottom);
+
+        let mode = self.get_blend_mode();
+        let buf_width = self.buffer_width() as usize;
+        let buffer = self.get_mut_draw_buffer();
+
+        for y in top..bottom {
+            for x in left..right {
+                // map the center of the pixel back to the source
+                let (dx, dy) = (x as f64 + 0.5 - center_x, y as f64 + 0.5 - center_y);
+                let src_x = (cos * dx + sin * dy + half_width).floor();
+                let src_y = (cos * dy - sin * dx + half_height).floor();
+
+                if src_x < 0. || src_y < 0. || src_x >= width as f64 || src_y >= height as f64 {
+                    continue;
+                }
+
+                let src = pixels[src_y as usize * width as usize + src_x as usize];
+
+                if src.a != 0 {
+                    blend_into(&mut buffer[y as usize * buf_width + x as usize], src, mode);
+                }
+            }
+        }
+    }
+
+    /// Draw provided pixels (row-major order) transformed by an affine `transform` matrix.
+    ///
+    /// The matrix is `[a, b, c, d, e, f]` and maps a point (x, y) of the pixels to
+    /// `(a * x + c * y + e, b * x + d * y + f)` in the buffer (like the CSS `matrix()` function),
+    /// so `[1., 0., 0., 1., 0., 0.]` draws the pixels with their top left corner at (0, 0)
+    /// and `[2., 0., 0., 2., x, y]` draws them twice as big at (x, y).
+    /// Uses nearest-neighbor sampling, fully transparent pixels are skipped.
+    /// Does nothing if the matrix can't be inverted (e.g. has zero scale) or `pixels` has the wrong length.
+    /// Does not panic if a part of the pixels isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, rgb::RGBA8, Canvas, DrawTarget};
+    /// let sprite = [color::RED, color::GREEN, color::BLUE, color::WHITE];
+    ///
+    /// let mut moved = Canvas::new(6, 6, color::BLACK);
+    /// let mut expected = moved.clone();
+    /// moved.draw_pixels_transformed(2, 2, &sprite, [1., 0., 0., 1., 3., 1.]);
+    /// expected.draw_pixels(3, 1, 2, 2, &sprite);
+    /// assert_eq!(moved, expected);
+    ///
+    /// let mut scaled = Canvas::new(6, 6, color::BLACK);
+    /// let mut expected = scaled.clone();
+    /// scaled.draw_pixels_transformed(2, 2, &sprite, [2., 0., 0., 2., 1., 1.]);
+    /// expected.draw_pixels_scaled(1, 1, 2, 2, &sprite, 2, 2);
+    /// assert_eq!(scaled, expected);
+    /// ```
+    fn draw_pixels_transformed(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+        transform: [f32; 6],
+    ) {
+        if pixels.len() != width as usize * height as usize || pixels.is_empty() {
+            return;
+        }
+
+        let [a, b, c, d, e, f] = transform.map(|value| value as f64);
+        let det = a * d - b * c;
+
+        if det == 0. || !det.is_finite() {
+            return;
+        }
+
+        // bounding box of the transformed corners
+        let (width_f, height_f) = (width as f64, height as f64);
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+        for (x, y) in [(0., 0.), (width_f, 0.), (0., height_f), (width_f, height_f)] {
+            let (x, y) = (a * x + c * y + e, b * x + d * y + f);
+
+            (min_x, min_y) = (min_x.min(x), min_y.min(y));
+            (max_x, max_y) = (max_x.max(x), max_y.max(y));
+        }
+
+        let (clip_left, clip_top, clip_right, clip_bottom) = clip_bounds(self);
+        let left = (min_x.floor() as i64).max(clip_left);
+        let top = (min_y.floor() as i64).max(clip_top);
+        let right = (max_x.ceil() as i64).min(clip_right);
+        let bottom = (max_y.ceil() as i64).min(clip_bottom);
+
+        let mode = self.get_blend_mode();
+        let buf_width = self.buffer_width() as usize;
+        let buffer = self.get_mut_draw_buffer();
+
+        for y in top..bottom {
+            for x in left..right {
+                // map the center of the pixel back to the source with the inverse matrix
+                let (dx, dy) = (x as f64 + 0.5 - e, y as f64 + 0.5 - f);
+                let src_x = ((d * dx - c * dy) / det).floor();
+                let src_y = ((a * dy - b * dx) / det).floor();
+
+                if src_x < 0. || src_y < 0. || src_x >= width_f || src_y >= height_f {
+                    continue;
+                }
+
+                let src = pixels[src_y as usize * width as usize + src_x as usize];
+
+                if src.a != 0 {
+                    blend_into(&mut buffer[y as usize * buf_width + x as usize], src, mode);
+                }
+            }
+        }
+    }
+
+    /// Draw a [`Canvas`] with its top left corner at (x, y).
+    ///
+    /// Does not panic if a part of the canvas isn't on screen, just draws the part that is.
+    #[inline]
+    fn draw_canvas(&mut self, x: i32, y: i32, canvas: &Canvas) {
+        self.draw_pixels(
+            x,
+            y,
+            canvas.buffer_width(),
+            canvas.buffer_height(),
+            canvas.get_draw_buffer(),
+        );
+    }
+
+    /// Draw a [`Canvas`] with its top left corner at (x, y), combining it with the buffer using `mode`
+    /// instead of the current blend mode.
+    ///
+    /// Can be used to composite layers, e.g. multiply a lighting layer onto the scene.
+    /// Does not panic if a part of the canvas isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, BlendMode, Canvas, DrawTarget};
+    /// let layer = Canvas::new(2, 2, RGBA8::new(0, 0, 0, 128));
+    /// let mut blended = Canvas::new(2, 2, RGBA8::new(200, 200, 200, 255));
+    /// let mut multiplied = blended.clone();
+    ///
+    /// blended.draw_canvas_blended(0, 0, &layer, BlendMode::AlphaBlend);
+    /// multiplied.draw_canvas_blended(0, 0, &layer, BlendMode::Multiply);
+    ///
+    /// // halfway to black either way, but multiplying keeps the buffer's alpha
+    /// assert_eq!(blended.get_pixel(0, 0), Some(RGBA8::new(100, 100, 100, 255)));
+    /// assert_eq!(multiplied.get_pixel(1, 1), Some(RGBA8::new(100, 100, 100, 255)));
+    ///
+    /// let white_layer = Canvas::new(2, 2, RGBA8::new(255, 255, 255, 128));
+    /// blended.draw_canvas_blended(0, 0, &white_layer, BlendMode::AlphaBlend);
+    /// multiplied.draw_canvas_blended(0, 0, &white_layer, BlendMode::Multiply);
+    ///
+    /// // multiplying by white changes nothing
+    /// assert_eq!(blended.get_pixel(0, 0), Some(RGBA8::new(178, 178, 178, 255)));
+    /// assert_eq!(multiplied.get_pixel(0, 0), Some(RGBA8::new(100, 100, 100, 255)));
+    /// ```
+    fn draw_canvas_blended(&mut self, x: i32, y: i32, canvas: &Canvas, mode: BlendMode) {
+        let (width, height) = (canvas.buffer_width(), canvas.buffer_height());
+
+        if let (Some(buffer), Some((dest_pos, src_pos, size))) = (
+            GenericSurface::new(canvas.get_draw_buffer(), [width, height].into()),
+            clip_rect(self, x, y, width, height),
+        ) {
+            blit_with_mode(
+                self,
+                dest_pos,
+                buffer.sub_surface(src_pos, size),
+                &[],
+                None,
+                mode,
+            );
+        }
+    }
+
+    /// Draw text with its top left corner at (x, y).
+    ///
+    /// Only the set pixels of the glyphs are drawn (with `color`), the rest are left as is.
+    /// `'\n'` moves to the start of the next line and `'\t'` moves to the next tab stop (every 4 glyphs).
+    /// Characters the font doesn't have are skipped, but still take up space.
+    /// Does not panic if a part of the text isn't on screen, just draws the part that is.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Canvas, DrawTarget, Font};
+    /// let mut canvas = Canvas::new(40, 16, color::BLACK);
+    /// canvas.draw_text(0, 0, "\t_\n_", color::WHITE, &Font::default());
+    ///
+    /// // the underscore is the bottom row of the glyph
+    /// assert_eq!(canvas.get_pixel(31, 7), Some(color::BLACK));
+    /// assert_eq!(canvas.get_pixel(32, 7), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(0, 15), Some(color::WHITE));
+    /// assert_eq!(canvas.get_pixel(8, 15), Some(color::BLACK));
+    /// ```
+    fn draw_text(&mut self, x: i32, y: i32, text: &str, color: RGBA8, font: &Font) {
+        let (glyph_width, glyph_height) = (font.glyph_width(), font.glyph_height());
+
+        for (column, line, glyph) in font.layout(text) {
+            let glyph_x = x as i64 + (column * glyph_width) as i64;
+            let glyph_y = y as i64 + (line * glyph_height) as i64;
+
+            for (idx, _) in glyph.iter().enumerate().filter(|(_, &set)| set) {
+                let pix_x = glyph_x + (idx as u32 % glyph_width) as i64;
+                let pix_y = glyph_y + (idx as u32 / glyph_width) as i64;
+
+                if let (Ok(pix_x), Ok(pix_y)) = (pix_x.try_into(), pix_y.try_into()) {
+                    self.draw_pixel(pix_x, pix_y, color);
+                }
+            }
+        }
+    }
+
+    /// Replace the color of the area connected to (x, y) (not diagonally) with `color`.
+    ///
+    /// The area consists of all the pixels of the same color as the one at (x, y).
+    /// The pixels are always replaced regardless of the current [`BlendMode`].
+    /// Does nothing if the position is outside the screen.
+    fn flood_fill(&mut self, x: i32, y: i32, color: RGBA8) {
+        let target = match clipped_pixel_index(self, x, y) {
+            Some(idx) => self.get_draw_buffer()[idx],
+            None => return,
+        };
+
+        if target == color {
+            return;
+        }
+
+        let (left, top, right, bottom) = clip_bounds(self);
+        let width = self.buffer_width() as usize;
+        let buffer = self.get_mut_draw_buffer();
+
+        // scanline fill: every stack entry is a seed for a horizontal span
+        let mut stack = vec![(x as i64, y as i64)];
+
+        while let Some((x, y)) = stack.pop() {
+            let row = y as usize * width;
+
+            if buffer[row + x as usize] != target {
+                continue;
+            }
+
+            let mut span_left = x;
+            while span_left > left && buffer[row + span_left as usize - 1] == target {
+                span_left -= 1;
+            }
+
+            let mut span_right = x;
+            while span_right + 1 < right && buffer[row + span_right as usize + 1] == target {
+                span_right += 1;
+            }
+
+            buffer[row + span_left as usize..=row + span_right as usize].fill(color);
+
+            for next_y in [y - 1, y + 1] {
+                if next_y < top || next_y >= bottom {
+                    continue;
+                }
+
+                let next_row = next_y as usize * width;
+                let mut in_span = false;
+
+                for next_x in span_left..=span_right {
+                    if buffer[next_row + next_x as usize] == target {
+                        if !in_span {
+                            stack.push((next_x, next_y));
+                            in_span = true;
+                        }
+                    } else {
+                        in_span = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy a rectangle of the buffer to another position in the buffer.
+    ///
+    /// The rectangles can overlap. The pixels are copied as-is, ignoring the blend mode (but not the clip rectangle).
+    /// Does not panic if a part of either rectangle isn't on screen, just copies the part that is.
+    fn copy_region(
+        &mut self,
+        src_x: i32,
+        src_y: i32,
+        width: u32,
+        height: u32,
+        dst_x: i32,
+        dst_y: i32,
+    ) {
+        let (src_x, src_y) = (src_x as i64, src_y as i64);
+        let (dst_x, dst_y) = (dst_x as i64, dst_y as i64);
+
+        // clip the source to the buffer first
+        let src_left = src_x.max(0);
+        let src_top = src_y.max(0);
+        let src_right = (src_x + width as i64).min(self.buffer_width() as i64);
+        let src_bottom = (src_y + height as i64).min(self.buffer_height() as i64);
+
+        if src_left >= src_right || src_top >= src_bottom {
+            return;
+        }
+
+        let dest_x = dst_x + (src_left - src_x);
+        let dest_y = dst_y + (src_top - src_y);
+
+        // then clip the destination (this can't create a rectangle bigger than the source)
+        let (dest_pos, offset, size) = match clip_rect(
+            self,
+            dest_x.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            dest_y.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            (src_right - src_left) as u32,
+            (src_bottom - src_top) as u32,
+        ) {
+            Some(clipped) => clipped,
+            None => return,
+        };
+
+        let src_left = (src_left + offset.x as i64) as usize;
+        let src_top = (src_top + offset.y as i64) as usize;
+        let width = self.buffer_width() as usize;
+        let buffer = self.get_mut_draw_buffer();
+
+        let mut copy_row = |iy: usize| {
+            let src_start = (src_top + iy) * width + src_left;
+            let dest_start = (dest_pos.y as usize + iy) * width + dest_pos.x as usize;
+
+            buffer.copy_within(src_start..src_start + size.x as usize, dest_start);
+        };
+
+        // copy the rows in the order that doesn't overwrite source rows before they're copied
+        if dest_pos.y as usize > src_top {
+            (0..size.y as usize).rev().for_each(&mut copy_row);
+        } else {
+            (0..size.y as usize).for_each(&mut copy_row);
+        }
+    }
+
+    /// Replace every pixel of the buffer with the closest color of the palette (see [`Palette::nearest_index()`]).
+    ///
+    /// The clip rectangle and the blend mode are ignored.
+    #[inline]
+    fn apply_palette(&mut self, palette: &Palette) {
+        palette.apply(self.get_mut_draw_buffer());
+    }
+
+    /// Replace every pixel of the buffer with the result of `f` called with its position and current color.
+    ///
+    /// The pixels are visited in row-major order. The clip rectangle and the blend mode are ignored.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(4, 4, RGBA8::default());
+    /// canvas.map_pixels(|x, y, _| RGBA8::new(x as u8 * 60, y as u8 * 60, 7, 255));
+    /// let original = canvas.clone();
+    ///
+    /// let invert = |_, _, pix: RGBA8| RGBA8::new(255 - pix.r, 255 - pix.g, 255 - pix.b, pix.a);
+    /// canvas.map_pixels(invert);
+    /// assert_ne!(canvas, original);
+    ///
+    /// canvas.map_pixels(invert);
+    /// assert_eq!(canvas, original);
+    /// ```
+    fn map_pixels<F: FnMut(i32, i32, RGBA8) -> RGBA8>(&mut self, mut f: F)
+    where
+        Self: Sized,
+    {
+        let width = self.buffer_width() as usize;
+
+        if width == 0 {
+            return;
+        }
+
+        for (y, row) in self
+            .get_mut_draw_buffer()
+            .chunks_exact_mut(width)
+            .enumerate()
+        {
+            for (x, pix) in row.iter_mut().enumerate() {
+                *pix = f(x as i32, y as i32, *pix);
+            }
+        }
+    }
+
+    /// Convert the whole buffer to grayscale, keeping the alpha channel.
+    ///
+    /// Uses the luma weights `0.299 R + 0.587 G + 0.114 B`. The clip rectangle and the blend mode are ignored.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, rgb::RGBA8, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(1, 1, color::RED);
+    /// canvas.grayscale();
+    ///
+    /// assert_eq!(canvas.get_pixel(0, 0), Some(RGBA8::new(76, 76, 76, 255)));
+    /// ```
+    fn grayscale(&mut self) {
+        for pix in self.get_mut_draw_buffer() {
+            let luma = (pix.r as u32 * 299 + pix.g as u32 * 587 + pix.b as u32 * 114 + 500) / 1000;
+            let luma = luma as u8;
+
+            *pix = RGBA8::new(luma, luma, luma, pix.a);
+        }
+    }
+
+    /// Invert the color channels of the whole buffer, keeping the alpha channel.
+    ///
+    /// The clip rectangle and the blend mode are ignored.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(1, 1, RGBA8::new(10, 100, 200, 128));
+    ///
+    /// canvas.invert();
+    /// assert_eq!(canvas.get_pixel(0, 0), Some(RGBA8::new(245, 155, 55, 128)));
+    ///
+    /// canvas.invert();
+    /// assert_eq!(canvas.get_pixel(0, 0), Some(RGBA8::new(10, 100, 200, 128)));
+    /// ```
+    fn invert(&mut self) {
+        for pix in self.get_mut_draw_buffer() {
+            *pix = RGBA8::new(255 - pix.r, 255 - pix.g, 255 - pix.b, pix.a);
+        }
+    }
+
+    /// Reduce the color channels of a rectangle to `levels` evenly spaced values using ordered (4×4 Bayer) dithering.
+    ///
+    /// The alpha channel is kept. Values of `levels` less than 2 are treated as 2.
+    /// The blend mode is ignored.
+    /// Does not panic if a part of the rectangle isn't on screen, just dithers the part that is.
+    fn dither_region(&mut self, x: i32, y: i32, width: u32, height: u32, levels: u8) {
+        const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+        let steps = levels.max(2) as u32 - 1;
+
+        if let Some((dest_pos, _, size)) = clip_rect(self, x, y, width, height) {
+            let buf_width = self.buffer_width() as usize;
+            let buffer = self.get_mut_draw_buffer();
+
+            for py in dest_pos.y..dest_pos.y + size.y {
+                for px in dest_pos.x..dest_pos.x + size.x {
+                    // threshold in (0, 1) as a fraction of 32
+                    let threshold = BAYER[py as usize % 4][px as usize % 4] * 2 + 1;
+
+                    let quantize = |c: u8| {
+                        let level =
+                            ((c as u32 * steps * 32 + threshold * 255) / (255 * 32)).min(steps);
+                        ((level * 255 + steps / 2) / steps) as u8
+                    };
+
+                    let pix = &mut buffer[py as usize * buf_width + px as usize];
+                    *pix = RGBA8::new(quantize(pix.r), quantize(pix.g), quantize(pix.b), pix.a);
+                }
+            }
+        }
+    }
+
+    /// Blur the whole buffer with a box blur `radius * 2 + 1` pixels wide, the alpha channel is blurred too.
+    ///
+    /// Pixels beyond the edges of the buffer are treated as copies of the edge pixels.
+    /// The cost doesn't depend on the radius, but this still goes over the whole buffer twice,
+    /// so it's a lot slower than most other drawing functions.
+    /// The clip rectangle and the blend mode are ignored. A `radius` of 0 does nothing.
+    ///
+    /// ```
+    /// # use simple_pixels::{rgb::RGBA8, Canvas, DrawTarget};
+    /// let mut canvas = Canvas::new(7, 7, RGBA8::default());
+    /// canvas.draw_pixel(3, 3, RGBA8::new(225, 225, 225, 225));
+    ///
+    /// canvas.blur(1);
+    ///
+    /// // spread evenly over the 3×3 square around it
+    /// for (x, y) in [(2, 2), (3, 2), (4, 2), (2, 3), (3, 3), (4, 3), (2, 4), (3, 4), (4, 4)] {
+    ///     assert_eq!(canvas.get_pixel(x, y), Some(RGBA8::new(25, 25, 25, 25)));
+    /// }
+    ///
+    /// assert_eq!(canvas.get_pixel(1, 3), Some(RGBA8::default()));
+    /// assert_eq!(canvas.get_pixel(5, 3), Some(RGBA8::default()));
+    /// ```
+    fn blur(&mut self, radius: u32) {
+        let (width, height) = (self.buffer_width(), self.buffer_height());
+        box_blur(
+            self.get_mut_draw_buffer(),
+            &mut Vec::new(),
+            width,
+            height,
+            radius,
+        );
+    }
+
+    /// Move the buffer's contents by (dx, dy) pixels, filling the uncovered area with `fill`.
+    ///
+    /// The clip rectangle and the blend mode are ignored.
+    fn scroll(&mut self, dx: i32, dy: i32, fill: RGBA8) {
+        let (width, height) = (self.buffer_width() as i64, self.buffer_height() as i64);
+        let (dx, dy) = (dx as i64, dy as i64);
+        let buffer = self.get_mut_draw_buffer();
+
+        if dx.abs() >= width || dy.abs() >= height {
+            buffer.fill(fill);
+            return;
+        }
+
+        // moving the whole buffer at once also moves every row correctly,
+        // the pixels that wrap around to adjacent rows end up in the uncovered columns
+        let len = buffer.len();
+        let offset = dy * width + dx;
+
+        if offset > 0 {
+            buffer.copy_within(..len - offset as usize, offset as usize);
+        } else {
+            buffer.copy_within((-offset) as usize.., 0);
+        }
+
+        let (width, height) = (width as usize, height as usize);
+
+        let uncovered_rows = if dy >= 0 {
+            0..dy as usize
+        } else {
+            height - (-dy) as usize..height
+        };
+        buffer[uncovered_rows.start * width..uncovered_rows.end * width].fill(fill);
+
+        let uncovered_columns = if dx >= 0 {
+            0..dx as usize
+        } else {
+            width - (-dx) as usize..width
+        };
+
+        if !uncovered_columns.is_empty() {
+            for row in buffer.chunks_exact_mut(width) {
+                row[uncovered_columns.clone()].fill(fill);
+            }
+        }
+    }
+
+    /// Fill the entire buffer at once.
+    ///
+    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
+    fn draw_screen(&mut self, pixels: &[RGBA8]) {
+        let (width, height) = (self.buffer_width(), self.buffer_height());
+
+        // nothing to blend or clip, just copy the pixels
+        if self.get_clip().is_none()
+            && self.get_blend_mode() == BlendMode::Replace
+            && pixels.len() == self.get_draw_buffer().len()
+        {
+            copy_buffer(self.get_mut_draw_buffer(), pixels);
+            return;
+        }
+
+        if let (Some(buffer), Some((dest_pos, src_pos, size))) = (
+            simple_blit::GenericSurface::new(pixels, simple_blit::size(width, height)),
+            clip_rect(self, 0, 0, width, height),
+        ) {
+            blit_blended(self, dest_pos, buffer.sub_surface(src_pos, size), &[], None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    /// The positions of the pixels that aren't `background`.
+    fn drawn_pixels(canvas: &Canvas, background: RGBA8) -> Vec<(i32, i32)> {
+        let width = canvas.width() as usize;
+
+        canvas
+            .pixels()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &pix)| pix != background)
+            .map(|(idx, _)| ((idx % width) as i32, (idx / width) as i32))
+            .collect()
+    }
+
+    #[test]
+    fn draw_pixel_ignores_outside() {
+        let mut canvas = Canvas::new(4, 4, color::BLACK);
+        canvas.draw_pixel(-1, 0, color::RED);
+        canvas.draw_pixel(0, 4, color::RED);
+        canvas.draw_pixel(3, 3, color::RED);
+
+        assert_eq!(drawn_pixels(&canvas, color::BLACK), [(3, 3)]);
+    }
+
+    #[test]
+    fn draw_rect_clips_to_buffer() {
+        let mut canvas = Canvas::new(4, 4, color::BLACK);
+        canvas.draw_rect(-2, 2, 4, 10, color::RED);
+
+        assert_eq!(
+            drawn_pixels(&canvas, color::BLACK),
+            [(0, 2), (1, 2), (0, 3), (1, 3)]
+        );
+    }
+
+    /// The points of a line from the plain Bresenham loop, without any clipping.
+    fn line_points(x0: i64, y0: i64, x1: i64, y1: i64) -> Vec<(i32, i32)> {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        let mut points = vec![(x as i32, y as i32)];
+
+        while (x, y) != (x1, y1) {
+            let err2 = 2 * err;
+
+            if err2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+
+            if err2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+
+            points.push((x as i32, y as i32));
+        }
+
+        points
+    }
+
+    #[test]
+    fn draw_line_octants_and_end_points() {
+        let lines = [
+            // a single point
+            ((3, 3), (3, 3), vec![(3, 3)]),
+            // horizontal and vertical, both directions
+            ((1, 2), (4, 2), vec![(1, 2), (2, 2), (3, 2), (4, 2)]),
+            ((4, 2), (1, 2), vec![(1, 2), (2, 2), (3, 2), (4, 2)]),
+            ((5, 0), (5, 3), vec![(5, 0), (5, 1), (5, 2), (5, 3)]),
+            ((5, 3), (5, 0), vec![(5, 0), (5, 1), (5, 2), (5, 3)]),
+            // 45° in all four directions
+            ((0, 0), (3, 3), vec![(0, 0), (1, 1), (2, 2), (3, 3)]),
+            ((3, 3), (0, 0), vec![(0, 0), (1, 1), (2, 2), (3, 3)]),
+            ((0, 3), (3, 0), vec![(0, 3), (1, 2), (2, 1), (3, 0)]),
+            ((3, 0), (0, 3), vec![(0, 3), (1, 2), (2, 1), (3, 0)]),
+            // shallow and steep, halfway points round towards the end point
+            ((0, 0), (4, 2), vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]),
+            ((0, 0), (2, 4), vec![(0, 0), (1, 1), (1, 2), (2, 3), (2, 4)]),
+        ];
+
+        for ((x0, y0), (x1, y1), expected) in lines {
+            let mut canvas = Canvas::new(8, 8, color::BLACK);
+            canvas.draw_line(x0, y0, x1, y1, color::WHITE);
+
+            let mut drawn = drawn_pixels(&canvas, color::BLACK);
+            drawn.sort_unstable();
+            let mut expected = expected;
+            expected.sort_unstable();
+
+            assert_eq!(drawn, expected, "line ({x0}, {y0}) to ({x1}, {y1})");
+        }
+    }
+
+    #[test]
+    fn draw_line_clipped_matches_unclipped() {
+        let range = -4..12;
+
+        for (x0, y0) in range
+            .clone()
+            .flat_map(|x| range.clone().map(move |y| (x, y)))
+        {
+            for (x1, y1) in range
+                .clone()
+                .flat_map(|x| range.clone().map(move |y| (x, y)))
+            {
+                let mut canvas = Canvas::new(8, 6, color::BLACK);
+                canvas.draw_line(x0, y0, x1, y1, color::WHITE);
+
+                let mut expected: Vec<_> = line_points(x0 as i64, y0 as i64, x1 as i64, y1 as i64)
+                    .into_iter()
+                    .filter(|&(x, y)| (0..8).contains(&x) && (0..6).contains(&y))
+                    .collect();
+                expected.sort_unstable();
+                let mut drawn = drawn_pixels(&canvas, color::BLACK);
+                drawn.sort_unstable();
+
+                assert_eq!(drawn, expected, "line ({x0}, {y0}) to ({x1}, {y1})");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_line_extreme_end_points() {
+        let mut canvas = Canvas::new(8, 4, color::BLACK);
+        canvas.draw_line(i32::MIN, 1, i32::MAX, 1, color::WHITE);
+        assert_eq!(
+            drawn_pixels(&canvas, color::BLACK),
+            (0..8).map(|x| (x, 1)).collect::<Vec<_>>()
+        );
+
+        let mut canvas = Canvas::new(8, 4, color::BLACK);
+        canvas.draw_line(2, i32::MAX, 2, i32::MIN, color::WHITE);
+        assert_eq!(
+            drawn_pixels(&canvas, color::BLACK),
+            (0..4).map(|y| (2, y)).collect::<Vec<_>>()
+        );
+
+        let mut canvas = Canvas::new(8, 4, color::BLACK);
+        canvas.draw_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, color::WHITE);
+        assert_eq!(
+            drawn_pixels(&canvas, color::BLACK),
+            (0..4).map(|i| (i, i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn draw_circle_filled_area() {
+        for radius in [5, 10, 25, 50] {
+            let mut canvas = Canvas::new(128, 128, color::BLACK);
+            canvas.draw_circle_filled(64, 64, radius, color::WHITE);
+
+            let area = drawn_pixels(&canvas, color::BLACK).len() as f64;
+            let expected = std::f64::consts::PI * (radius as f64 + 0.5).powi(2);
+
+            assert!(
+                (area - expected).abs() / expected < 0.05,
+                "radius {radius}: area {area}, expected about {expected}"
+            );
+        }
+
+        // the boundary is included
+        let mut canvas = Canvas::new(32, 32, color::BLACK);
+        canvas.draw_circle_filled(16, 16, 10, color::WHITE);
+        assert_eq!(drawn_pixels(&canvas, color::BLACK).len(), 349);
+    }
+
+    #[test]
+    fn draw_circle_filled_covers_outline() {
+        for radius in 0..20 {
+            let mut outline = Canvas::new(48, 48, color::BLACK);
+            outline.draw_circle(24, 24, radius, color::WHITE);
+            let mut filled = Canvas::new(48, 48, color::BLACK);
+            filled.draw_circle_filled(24, 24, radius, color::WHITE);
+
+            let filled_pixels = drawn_pixels(&filled, color::BLACK);
+
+            for pixel in drawn_pixels(&outline, color::BLACK) {
+                assert!(filled_pixels.contains(&pixel), "radius {radius}: {pixel:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_circle_filled_symmetry() {
+        for radius in 0..20 {
+            let mut canvas = Canvas::new(48, 48, color::BLACK);
+            canvas.draw_circle_filled(24, 24, radius, color::WHITE);
+
+            let pixels = drawn_pixels(&canvas, color::BLACK);
+            let is_drawn = |x: i32, y: i32| pixels.contains(&(24 + x, 24 + y));
+
+            for &(x, y) in &pixels {
+                let (x, y) = (x - 24, y - 24);
+
+                for (x, y) in [(x, y), (y, x)] {
+                    assert!(
+                        is_drawn(x, y) && is_drawn(-x, y) && is_drawn(x, -y) && is_drawn(-x, -y)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_pixel_blended_half_alpha() {
+        let mut canvas = Canvas::new(3, 1, RGBA8::new(0, 0, 200, 255));
+        canvas.draw_pixel_blended(0, 0, RGBA8::new(255, 0, 0, 128));
+        canvas.draw_pixel_blended(1, 0, RGBA8::new(255, 0, 0, 0));
+        canvas.draw_pixel_blended(2, 0, RGBA8::new(255, 0, 0, 255));
+
+        assert_eq!(
+            canvas.pixels(),
+            [
+                RGBA8::new(128, 0, 100, 255),
+                RGBA8::new(0, 0, 200, 255),
+                RGBA8::new(255, 0, 0, 255),
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_rect_outline_keeps_interior() {
+        let mut canvas = Canvas::new(10, 8, color::BLACK);
+        canvas.draw_rect_outline(1, 1, 8, 6, 2, color::WHITE);
+
+        for y in 0..8 {
+            for x in 0..10 {
+                let inside_rect = (1..9).contains(&x) && (1..7).contains(&y);
+                let interior = (3..7).contains(&x) && (3..5).contains(&y);
+                let expected = if inside_rect && !interior {
+                    color::WHITE
+                } else {
+                    color::BLACK
+                };
+
+                assert_eq!(canvas.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+
+        // too thick to leave an interior
+        let mut canvas = Canvas::new(10, 8, color::BLACK);
+        canvas.draw_rect_outline(1, 1, 8, 6, 3, color::WHITE);
+        let mut filled = Canvas::new(10, 8, color::BLACK);
+        filled.draw_rect(1, 1, 8, 6, color::WHITE);
+        assert_eq!(canvas, filled);
+    }
+
+    #[test]
+    fn draw_pixels_keyed_skips_key() {
+        const KEY: RGBA8 = RGBA8::new(255, 0, 255, 255);
+        let background = RGBA8::new(0, 0, 100, 255);
+
+        let mut canvas = Canvas::new(4, 3, background);
+        #[rustfmt::skip]
+        canvas.draw_pixels_keyed(1, 1, 2, 2, &[
+            color::RED, KEY,
+            KEY, color::WHITE,
+        ], KEY);
+
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            background, background, background, background,
+            background, color::RED, background, background,
+            background, background, color::WHITE, background,
+        ]);
+    }
+
+    /// An asymmetric 2×2 sprite.
+    const SPRITE: [RGBA8; 4] = [color::RED, color::GREEN, color::BLUE, color::WHITE];
+
+    #[test]
+    fn draw_pixels_flipped_orientation() {
+        let [a, b, c, d] = SPRITE;
+
+        for (flip_x, flip_y, expected) in [
+            (false, false, [a, b, c, d]),
+            (true, false, [b, a, d, c]),
+            (false, true, [c, d, a, b]),
+            (true, true, [d, c, b, a]),
+        ] {
+            let mut canvas = Canvas::new(2, 2, color::BLACK);
+            canvas.draw_pixels_flipped(0, 0, 2, 2, &SPRITE, flip_x, flip_y);
+            assert_eq!(
+                canvas.pixels(),
+                expected,
+                "flip_x {flip_x}, flip_y {flip_y}"
+            );
+        }
+    }
+
+    #[test]
+    fn draw_pixels_flipped_both_is_rotated_180() {
+        let mut flipped = Canvas::new(4, 4, color::BLACK);
+        flipped.draw_pixels_flipped(1, 1, 2, 2, &SPRITE, true, true);
+
+        let mut rotated = Canvas::new(4, 4, color::BLACK);
+        rotated.draw_pixels_rotated(2, 2, 2, 2, &SPRITE, std::f32::consts::PI);
+
+        assert_eq!(flipped, rotated);
+    }
+
+    #[test]
+    fn draw_pixels_flipped_clipped() {
+        let [a, b, c, d] = SPRITE;
+
+        // only the bottom right pixel of the flipped sprite is visible
+        for (flip_x, flip_y, expected) in [
+            (false, false, d),
+            (true, false, c),
+            (false, true, b),
+            (true, true, a),
+        ] {
+            let mut canvas = Canvas::new(2, 2, color::BLACK);
+            canvas.draw_pixels_flipped(-1, -1, 2, 2, &SPRITE, flip_x, flip_y);
+            assert_eq!(
+                canvas.pixels(),
+                [expected, color::BLACK, color::BLACK, color::BLACK],
+                "flip_x {flip_x}, flip_y {flip_y}"
+            );
+        }
+
+        // the left column of the flipped sprite is visible at the right edge
+        let mut canvas = Canvas::new(2, 2, color::BLACK);
+        canvas.draw_pixels_flipped(1, 0, 2, 2, &SPRITE, true, false);
+        assert_eq!(canvas.pixels(), [color::BLACK, b, color::BLACK, d]);
+    }
+
+    #[test]
+    fn flood_fill_stops_at_wall() {
+        let mut canvas = Canvas::new(8, 5, color::BLACK);
+        // a 1 pixel wide wall with a diagonal step, which a 4-connected fill can't pass
+        canvas.draw_line(3, 0, 3, 2, color::WHITE);
+        canvas.draw_line(4, 3, 4, 4, color::WHITE);
+        let before = canvas.clone();
+
+        canvas.flood_fill(0, 0, color::RED);
+
+        for y in 0..5 {
+            for x in 0..8 {
+                let left = x < 3 || (x < 4 && y >= 3);
+                let expected = if left {
+                    color::RED
+                } else {
+                    before.get_pixel(x, y).unwrap()
+                };
+                assert_eq!(canvas.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+
+        // outside the canvas or already the fill color
+        let filled = canvas.clone();
+        canvas.flood_fill(-1, 0, color::GREEN);
+        canvas.flood_fill(0, 5, color::GREEN);
+        canvas.flood_fill(0, 0, color::RED);
+        assert_eq!(canvas, filled);
+    }
+
+    #[test]
+    fn gradient_covers_every_value() {
+        let mut canvas = Canvas::new(256, 1, color::RED);
+        canvas.draw_gradient_h(0, 0, 256, 1, color::BLACK, color::WHITE);
+
+        for (i, &pix) in canvas.pixels().iter().enumerate() {
+            let value = i as u8;
+            assert_eq!(pix, RGBA8::new(value, value, value, 255));
+        }
+
+        let mut canvas = Canvas::new(1, 256, color::RED);
+        canvas.draw_gradient_v(
+            0,
+            0,
+            1,
+            256,
+            RGBA8::new(0, 0, 0, 255),
+            RGBA8::new(0, 0, 0, 0),
+        );
+
+        for (i, &pix) in canvas.pixels().iter().enumerate() {
+            assert_eq!(pix, RGBA8::new(0, 0, 0, 255 - i as u8));
+        }
+    }
+
+    /// A canvas where every pixel is different: the red and green channels are its position.
+    fn pattern(width: u32, height: u32) -> Canvas {
+        let pixels = (0..width * height)
+            .map(|idx| RGBA8::new((idx % width) as u8, (idx / width) as u8, 0, 255))
+            .collect();
+
+        Canvas::from_pixels(pixels, width, height).unwrap()
+    }
+
+    #[test]
+    fn scroll_shifts_and_fills() {
+        let (width, height) = (7, 5);
+        let source = pattern(width, height);
+
+        for (dx, dy) in [
+            (0, 0),
+            (2, 0),
+            (-3, 0),
+            (0, 1),
+            (0, -2),
+            (2, -1),
+            (-1, 3),
+            (7, 0),
+            (0, -9),
+            (i32::MIN, i32::MAX),
+        ] {
+            let mut canvas = source.clone();
+            canvas.scroll(dx, dy, color::WHITE);
+
+            for y in 0..height as i32 {
+                for x in 0..width as i32 {
+                    let (src_x, src_y) = (x as i64 - dx as i64, y as i64 - dy as i64);
+                    let expected = if (0..width as i64).contains(&src_x)
+                        && (0..height as i64).contains(&src_y)
+                    {
+                        source.get_pixel(src_x as i32, src_y as i32).unwrap()
+                    } else {
+                        color::WHITE
+                    };
+
+                    assert_eq!(
+                        canvas.get_pixel(x, y),
+                        Some(expected),
+                        "scroll ({dx}, {dy}) at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copy_region_overlapping() {
+        let (width, height) = (8, 6);
+        let source = pattern(width, height);
+
+        // overlapping copies in every direction, and partly outside the buffer
+        for (src, dst) in [
+            ((1, 1), (2, 2)),
+            ((2, 2), (1, 1)),
+            ((1, 2), (3, 2)),
+            ((3, 2), (1, 2)),
+            ((2, 1), (2, 3)),
+            ((2, 3), (2, 1)),
+            ((-2, -1), (0, 0)),
+            ((4, 3), (6, 5)),
+        ] {
+            let (region_width, region_height) = (4, 3);
+            let mut canvas = source.clone();
+            canvas.copy_region(src.0, src.1, region_width, region_height, dst.0, dst.1);
+
+            for y in 0..height as i32 {
+                for x in 0..width as i32 {
+                    let (ix, iy) = (x - dst.0, y - dst.1);
+                    let in_region = (0..region_width as i32).contains(&ix)
+                        && (0..region_height as i32).contains(&iy);
+
+                    let expected = in_region
+                        .then(|| source.get_pixel(src.0 + ix, src.1 + iy))
+                        .flatten()
+                        .unwrap_or_else(|| source.get_pixel(x, y).unwrap());
+
+                    assert_eq!(
+                        canvas.get_pixel(x, y),
+                        Some(expected),
+                        "copy {src:?} to {dst:?} at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_fill_matches_serial() {
+        // big enough to take the parallel path, and not a multiple of the chunk size
+        let len = PARALLEL_THRESHOLD + PARALLEL_CHUNK_SIZE / 2 + 7;
+        let mut seed = 0x2545_f491_u32;
+
+        for _ in 0..4 {
+            // xorshift, so that the colors are arbitrary but the test is reproducible
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let [r, g, b, a] = seed.to_le_bytes();
+            let color = RGBA8::new(r, g, b, a);
+
+            let mut parallel = vec![color::BLACK; len];
+            fill_buffer(&mut parallel, color);
+            let mut serial = vec![color::BLACK; len];
+            serial.fill(color);
+            assert!(parallel == serial, "fill with {color:?}");
+
+            let src: Vec<_> = (0..len as u32)
+                .map(|i| RGBA8::from(i.wrapping_mul(seed).to_le_bytes()))
+                .collect();
+            copy_buffer(&mut parallel, &src);
+            assert!(parallel == src, "copy");
+        }
+    }
+
+    #[test]
+    fn draw_rect_full_width_fill() {
+        let (width, height) = (6, 5);
+
+        // full-width rectangles take the contiguous fill path, the others are filled row by row
+        for (x, y, rect_width, rect_height) in [
+            (0, 0, 6, 5),
+            (0, 1, 6, 2),
+            (-2, -1, 10, 3),
+            (-1, 3, 8, 9),
+            (0, 1, 5, 2),
+            (1, 1, 6, 2),
+        ] {
+            let mut canvas = pattern(width, height);
+            canvas.draw_rect(x, y, rect_width, rect_height, color::WHITE);
+
+            let mut expected = pattern(width, height);
+            for iy in y..y + rect_height as i32 {
+                for ix in x..x + rect_width as i32 {
+                    expected.draw_pixel(ix, iy, color::WHITE);
+                }
+            }
+
+            assert_eq!(
+                canvas, expected,
+                "rect ({x}, {y}) {rect_width}x{rect_height}"
+            );
+        }
+    }
+
+    #[test]
+    fn draw_pixels_batch_matches_sequential() {
+        let sprite: Vec<_> = (0..6)
+            .map(|i| RGBA8::new(i * 40, 255 - i * 40, i, 255))
+            .collect();
+        let items: [(i32, i32, u32, u32, &[RGBA8]); 5] = [
+            (1, 1, 3, 2, &sprite),
+            (2, 2, 2, 3, &sprite),
+            (-1, -1, 3, 2, &sprite),
+            (6, 4, 3, 2, &sprite),
+            (20, 0, 3, 2, &sprite),
+        ];
+
+        let mut batched = pattern(8, 6);
+        batched.draw_pixels_batch(&items);
+
+        let mut sequential = pattern(8, 6);
+        for (x, y, width, height, pixels) in items {
+            sequential.draw_pixels(x, y, width, height, pixels);
+        }
+
+        assert_eq!(batched, sequential);
+    }
+
+    #[test]
+    fn draw_pixels_rotated_zero_is_draw_pixels() {
+        let sprite: Vec<_> = (0..12)
+            .map(|i| RGBA8::new(i * 20, 0, 255 - i * 20, 255))
+            .collect();
+
+        for (width, height) in [(3, 4), (4, 3), (2, 6), (1, 1)] {
+            let sprite = &sprite[..(width * height) as usize];
+            let (cx, cy) = (4, 3);
+
+            let mut rotated = Canvas::new(9, 8, color::BLACK);
+            rotated.draw_pixels_rotated(cx, cy, width, height, sprite, 0.);
+
+            let mut expected = Canvas::new(9, 8, color::BLACK);
+            expected.draw_pixels(
+                cx - width as i32 / 2,
+                cy - height as i32 / 2,
+                width,
+                height,
+                sprite,
+            );
+
+            assert_eq!(rotated, expected, "{width}x{height}");
+        }
+    }
+
+    #[test]
+    fn draw_pixels_rotated_right_angle() {
+        let sprite: Vec<_> = (1..=8).map(|i| RGBA8::new(i * 30, 0, 0, 255)).collect();
+        let [a, b, c, d, e, f, g, h] = sprite[..] else {
+            unreachable!()
+        };
+        let o = color::BLACK;
+
+        // rotated clockwise about the center of the sprite, which is on the corner between four pixels
+        let mut canvas = Canvas::new(4, 5, o);
+        canvas.draw_pixels_rotated(2, 2, 4, 2, &sprite, std::f32::consts::FRAC_PI_2);
+
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            o, e, a, o,
+            o, f, b, o,
+            o, g, c, o,
+            o, h, d, o,
+            o, o, o, o,
+        ]);
+
+        let mut canvas = Canvas::new(4, 5, o);
+        canvas.draw_pixels_rotated(2, 2, 4, 2, &sprite, -std::f32::consts::FRAC_PI_2);
+
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            o, d, h, o,
+            o, c, g, o,
+            o, b, f, o,
+            o, a, e, o,
+            o, o, o, o,
+        ]);
+    }
+
+    #[test]
+    fn draw_pixels_scaled() {
+        let [a, b, c, d] = SPRITE;
+        let o = color::BLACK;
+
+        let mut scaled = pattern(6, 5);
+        scaled.draw_pixels_scaled(1, 2, 2, 2, &SPRITE, 1, 1);
+        let mut expected = pattern(6, 5);
+        expected.draw_pixels(1, 2, 2, 2, &SPRITE);
+        assert_eq!(scaled, expected);
+
+        let mut canvas = Canvas::new(5, 5, o);
+        canvas.draw_pixels_scaled(1, 0, 2, 2, &SPRITE, 2, 2);
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            o, a, a, b, b,
+            o, a, a, b, b,
+            o, c, c, d, d,
+            o, c, c, d, d,
+            o, o, o, o, o,
+        ]);
+
+        // a different scale for each axis
+        let mut canvas = Canvas::new(5, 6, o);
+        canvas.draw_pixels_scaled(0, 0, 2, 2, &SPRITE, 1, 3);
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            a, b, o, o, o,
+            a, b, o, o, o,
+            a, b, o, o, o,
+            c, d, o, o, o,
+            c, d, o, o, o,
+            c, d, o, o, o,
+        ]);
+
+        // clipped at the top left, the visible part starts in the middle of the blocks
+        let mut canvas = Canvas::new(4, 4, o);
+        canvas.draw_pixels_scaled(-1, -2, 2, 2, &SPRITE, 3, 3);
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), [
+            a, a, b, b,
+            c, c, d, d,
+            c, c, d, d,
+            c, c, d, d,
+        ]);
+    }
+
+    #[test]
+    fn dither_region_levels() {
+        // a 4×4 tile of every value, so that each one is tested in every position of the pattern
+        let gray = |value: u8| RGBA8::new(value, value, 255 - value, 100);
+        let source: Vec<_> = (0..4 * 1024)
+            .map(|idx| gray((idx % 1024 / 4) as u8))
+            .collect();
+        let source = Canvas::from_pixels(source, 1024, 4).unwrap();
+
+        let mut canvas = source.clone();
+        canvas.dither_region(0, 0, 1024, 4, 2);
+
+        for (&pix, &src) in canvas.pixels().iter().zip(source.pixels()) {
+            assert!(
+                [pix.r, pix.g, pix.b].iter().all(|&c| c == 0 || c == 255),
+                "{src:?} -> {pix:?}"
+            );
+            assert_eq!(pix.a, src.a);
+        }
+
+        // half of the pixels in a 4×4 tile of 50% gray are white
+        let mut canvas = Canvas::new(4, 4, RGBA8::new(128, 128, 128, 255));
+        canvas.dither_region(0, 0, 4, 4, 2);
+        assert_eq!(canvas.pixels().iter().filter(|pix| pix.r == 255).count(), 8);
+
+        let mut canvas = source.clone();
+        canvas.dither_region(0, 0, 1024, 4, 255);
+
+        for (&pix, &src) in canvas.pixels().iter().zip(source.pixels()) {
+            let close = |a: u8, b: u8| a.abs_diff(b) <= 1;
+            assert!(
+                close(pix.r, src.r) && close(pix.g, src.g) && close(pix.b, src.b),
+                "{src:?} -> {pix:?}"
+            );
+            assert_eq!(pix.a, src.a);
+        }
+    }
+
+    #[test]
+    fn draw_ellipse_symmetry() {
+        for (rx, ry) in [(0, 3), (4, 0), (1, 5), (7, 3), (10, 2), (6, 9)] {
+            for filled in [false, true] {
+                let mut canvas = Canvas::new(32, 32, color::BLACK);
+
+                if filled {
+                    canvas.draw_ellipse_filled(16, 16, rx, ry, color::WHITE);
+                } else {
+                    canvas.draw_ellipse(16, 16, rx, ry, color::WHITE);
+                }
+
+                let pixels = drawn_pixels(&canvas, color::BLACK);
+                let is_drawn = |x: i32, y: i32| pixels.contains(&(16 + x, 16 + y));
+
+                for &(x, y) in &pixels {
+                    let (x, y) = (x - 16, y - 16);
+
+                    assert!(x.unsigned_abs() <= rx && y.unsigned_abs() <= ry);
+                    assert!(
+                        is_drawn(-x, y) && is_drawn(x, -y) && is_drawn(-x, -y),
+                        "{rx}x{ry}, filled {filled}: ({x}, {y})"
+                    );
+                }
+
+                // the extreme points are on the ellipse
+                for (x, y) in [
+                    (rx as i32, 0),
+                    (-(rx as i32), 0),
+                    (0, ry as i32),
+                    (0, -(ry as i32)),
+                ] {
+                    assert!(is_drawn(x, y), "{rx}x{ry}, filled {filled}: ({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_ellipse_and_arc_match_circle() {
+        for radius in 0..16 {
+            let mut circle = Canvas::new(40, 40, color::BLACK);
+            circle.draw_circle(20, 20, radius, color::WHITE);
+
+            let mut ellipse = Canvas::new(40, 40, color::BLACK);
+            ellipse.draw_ellipse(20, 20, radius, radius, color::WHITE);
+            assert_eq!(ellipse, circle, "ellipse, radius {radius}");
+
+            for (start, end) in [(0., 360.), (90., 450.), (-180., 180.), (30., 400.)] {
+                let mut arc = Canvas::new(40, 40, color::BLACK);
+                arc.draw_arc(20, 20, radius, start, end, color::WHITE);
+                assert_eq!(arc, circle, "arc from {start} to {end}, radius {radius}");
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use rgb::RGBA8;
+use std::sync::OnceLock;
+
+/// Tab stops are placed every `TAB_WIDTH` glyphs.
+const TAB_WIDTH: u32 = 4;
+
+/// 8×8 glyphs for the printable ASCII characters (`' '` to `'~'`).
+///
+/// One byte per row, the lowest bit is the leftmost pixel.
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+/// A monospaced bitmap font.
+///
+/// [`Font::default()`] is an 8×8 font with the printable ASCII characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Font {
+    glyphs: Vec<bool>,
+    glyph_width: u32,
+    glyph_height: u32,
+    first_char: u32,
+    glyph_count: u32,
+}
+
+impl Font {
+    /// Create a font from an atlas of fixed-size glyphs (row-major order).
+    ///
+    /// The glyphs are laid out left to right, top to bottom, starting with `first_char`
+    /// and continuing with the consecutive characters. Pixels with nonzero alpha are set.
+    /// Returns `None` if the size of `pixels` isn't a multiple of `atlas_width` or the atlas can't fit a single glyph.
+    pub fn from_atlas(
+        pixels: &[RGBA8],
+        atlas_width: u32,
+        glyph_width: u32,
+        glyph_height: u32,
+        first_char: char,
+    ) -> Option<Self> {
+        if atlas_width == 0 || glyph_width == 0 || glyph_height == 0 {
+            return None;
+        }
+
+        if !pixels.len().is_multiple_of(atlas_width as usize) {
+            return None;
+        }
+
+        let atlas_height = (pixels.len() / atlas_width as usize) as u32;
+        let columns = atlas_width / glyph_width;
+        let glyph_count = columns * (atlas_height / glyph_height);
+
+        if glyph_count == 0 {
+            return None;
+        }
+
+        let mut glyphs = Vec::with_capacity((glyph_count * glyph_width * glyph_height) as usize);
+
+        for glyph in 0..glyph_count {
+            let (left, top) = (
+                glyph % columns * glyph_width,
+                glyph / columns * glyph_height,
+            );
+
+            for y in top..top + glyph_height {
+                let row = (y * atlas_width) as usize;
+
+                glyphs.extend(
+                    pixels[row + left as usize..row + (left + glyph_width) as usize]
+                        .iter()
+                        .map(|pix| pix.a != 0),
+                );
+            }
+        }
+
+        Some(Self {
+            glyphs,
+            glyph_width,
+            glyph_height,
+            first_char: first_char as u32,
+            glyph_count,
+        })
+    }
+
+    /// Width of a single glyph (in pixels).
+    #[inline]
+    pub fn glyph_width(&self) -> u32 {
+        self.glyph_width
+    }
+
+    /// Height of a single glyph (in pixels).
+    #[inline]
+    pub fn glyph_height(&self) -> u32 {
+        self.glyph_height
+    }
+
+    /// Returns the pixels of the glyph for `ch` (row-major order, `true` means set)
+    /// or `None` if the font doesn't have it.
+    #[inline]
+    pub fn glyph(&self, ch: char) -> Option<&[bool]> {
+        let index = (ch as u32).checked_sub(self.first_char)?;
+
+        if index >= self.glyph_count {
+            return None;
+        }
+
+        let size = (self.glyph_width * self.glyph_height) as usize;
+        let start = index as usize * size;
+
+        Some(&self.glyphs[start..start + size])
+    }
+
+    /// Returns the size of the text `(width, height)` as it would be drawn with [`DrawTarget::draw_text()`](crate::DrawTarget::draw_text).
+    ///
+    /// ```
+    /// # use simple_pixels::Font;
+    /// let font = Font::default();
+    ///
+    /// assert_eq!(font.measure("Hello"), (40, 8));
+    /// assert_eq!(font.measure("Hi\nthere"), (40, 16));
+    /// assert_eq!(font.measure("\tx"), (40, 8));
+    /// ```
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let (mut columns, mut lines) = (0, 0);
+
+        for line in text.split('\n') {
+            columns = columns.max(line.trim_end_matches('\r').chars().fold(0, advance));
+            lines += 1;
+        }
+
+        (columns * self.glyph_width, lines * self.glyph_height)
+    }
+
+    /// Positions of the glyphs of the text as `(column, line, glyph)`.
+    ///
+    /// Characters the font doesn't have are skipped, but still take up space.
+    pub(crate) fn layout<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = (u32, u32, &'a [bool])> + 'a {
+        text.split('\n').enumerate().flat_map(move |(line, text)| {
+            text.trim_end_matches('\r')
+                .chars()
+                .scan(0, |column, ch| {
+                    let start = *column;
+                    *column = advance(start, ch);
+
+                    Some((start, ch))
+                })
+                .filter(|&(_, ch)| ch != '\t')
+                .filter_map(move |(column, ch)| {
+                    self.glyph(ch).map(|glyph| (column, line as u32, glyph))
+                })
+        })
+    }
+}
+
+impl Default for Font {
+    /// An 8×8 font with the printable ASCII characters.
+    fn default() -> Self {
+        let glyphs = FONT_8X8
+            .iter()
+            .flatten()
+            .flat_map(|&row| (0..8).map(move |bit| row >> bit & 1 != 0))
+            .collect();
+
+        Self {
+            glyphs,
+            glyph_width: 8,
+            glyph_height: 8,
+            first_char: ' ' as u32,
+            glyph_count: FONT_8X8.len() as u32,
+        }
+    }
+}
+
+/// The shared [`Font::default()`], so that it isn't rebuilt every time it's needed.
+pub(crate) fn default_font() -> &'static Font {
+    static DEFAULT_FONT: OnceLock<Font> = OnceLock::new();
+
+    DEFAULT_FONT.get_or_init(Font::default)
+}
+
+/// The column after `ch` if it starts at `column`.
+#[inline]
+pub(crate) fn advance(column: u32, ch: char) -> u32 {
+    if ch == '\t' {
+        (column / TAB_WIDTH + 1) * TAB_WIDTH
+    } else {
+        column + 1
+    }
+}
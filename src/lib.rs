@@ -1,22 +1,52 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 #![warn(missing_docs)]
 
+#[cfg(feature = "gamepad")]
+pub use gilrs;
+#[cfg(feature = "image")]
+pub use image;
 pub use miniquad;
 pub use rgb;
 pub use simple_blit;
 
+pub mod color;
+pub mod noise;
+
+mod action;
+mod canvas;
+mod config;
+mod draw;
+mod font;
+mod palette;
+mod point;
+mod text;
+
+pub use action::{ActionMap, Binding};
+pub use canvas::Canvas;
+pub use config::ConfigBuilder;
+pub use draw::{BlendMode, DrawError, DrawTarget};
+pub use font::Font;
+#[cfg(feature = "gamepad")]
+pub use gilrs::{Axis as GamepadAxis, Button as GamepadButton};
+pub use palette::Palette;
+pub use point::{IPoint, Point};
+pub use text::TextWriter;
+
 use miniquad::{
     conf::Conf, window, Backend, Bindings, BufferLayout, BufferSource, BufferType, BufferUsage,
     CursorIcon, EventHandler, FilterMode, KeyCode, KeyMods, MipmapFilterMode, MouseButton,
-    PassAction, Pipeline, PipelineParams, RenderingBackend, ShaderMeta, ShaderSource,
-    TextureFormat, TextureId, TextureKind, TextureParams, TextureWrap, UniformBlockLayout,
-    VertexAttribute, VertexFormat,
+    PassAction, Pipeline, PipelineParams, RenderingBackend, ShaderError, ShaderId, ShaderMeta,
+    ShaderSource, TextureFormat, TextureId, TextureKind, TextureParams, TextureWrap, TouchPhase,
+    UniformBlockLayout, UniformDesc, UniformsSource, VertexAttribute, VertexFormat,
 };
 use rgb::{ComponentBytes, RGBA8};
-use rustc_hash::FxHashMap;
-use simple_blit::{GenericSurface, Surface};
+use rustc_hash::{FxHashMap, FxHashSet};
+use simple_blit::{GenericSurface, SubSurface};
+#[cfg(any(feature = "png", not(target_arch = "wasm32")))]
+use std::path::Path;
 use std::{
     future,
+    path::PathBuf,
     sync::{mpsc, Arc, Mutex},
     task::Poll,
     time::Duration,
@@ -61,7 +91,7 @@ void main() {
     gl_FragColor = texture2D(tex, texcoord);
 }"#;
 
-const SHADER_METAL: &str = r#"
+const SHADER_METAL_VERT: &str = r#"
 #include <metal_stdlib>
 
 using namespace metal;
@@ -86,7 +116,9 @@ vertex FragData vertexShader(
 
     return out;
 }
+"#;
 
+const SHADER_METAL_FRAG: &str = r#"
 fragment float4 fragmentShader(
     FragData in             [[stage_in]],
     texture2d<float> tex    [[texture(0)]],
@@ -96,7 +128,93 @@ fragment float4 fragmentShader(
 }
 "#;
 
+/// An error that occurred while saving a PNG file.
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub enum PngError {
+    /// Failed to create or write the file.
+    Io(std::io::Error),
+    /// Failed to encode the image.
+    Encoding(png::EncodingError),
+    /// Saving files isn't supported on this platform (web).
+    Unsupported,
+}
+
+#[cfg(feature = "png")]
+impl std::fmt::Display for PngError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Encoding(err) => write!(f, "png encoding error: {err}"),
+            Self::Unsupported => f.write_str("saving files is not supported on this platform"),
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl std::error::Error for PngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Encoding(err) => Some(err),
+            Self::Unsupported => None,
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<std::io::Error> for PngError {
+    #[inline]
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for PngError {
+    #[inline]
+    fn from(err: png::EncodingError) -> Self {
+        Self::Encoding(err)
+    }
+}
+
+/// How the framebuffer is scaled to the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Stretch the framebuffer over the whole window, ignoring its aspect ratio.
+    #[default]
+    Stretch,
+    /// Scale the framebuffer as much as possible while preserving its aspect ratio.
+    /// The rest of the window is filled with black bars.
+    Fit,
+    /// Like [`ScaleMode::Fit`], but only scale by whole multiples so that every framebuffer pixel has the same size.
+    /// Falls back to [`ScaleMode::Fit`] if the window is smaller than the framebuffer.
+    IntegerFit,
+}
+
+/// How the framebuffer is stored on the GPU.
+///
+/// The framebuffer itself is always [`RGBA8`], the format only affects the texture it's uploaded to every frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel, the pixels are uploaded as is.
+    #[default]
+    Rgba8,
+    /// 3 bytes per pixel, the alpha channel is dropped when uploading.
+    ///
+    /// Uploads a quarter less data, but the pixels have to be converted first.
+    /// Not supported on Metal, where [`PixelFormat::Rgba8`] is used instead.
+    Rgb8,
+}
+
+/// Identifies an off-screen layer created with [`Context::create_layer()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayerId(usize);
+
 /// Input state of a mouse/keyboard button
+///
+/// [`InputState::Pressed`] and [`InputState::Released`] last for a single frame,
+/// `update()`, `fixed_update()` and `draw()` of that frame all see them.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum InputState {
     /// The button has just been pressed.
@@ -107,37 +225,215 @@ pub enum InputState {
     Released,
 }
 
-/// An object that holds the app's global state.
-pub struct Context {
+/// An input event, see [`Context::drain_events()`].
+///
+/// Positions are in screen coords.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    /// A key has been pressed (or repeated if `repeat` is `true`).
+    KeyDown {
+        /// The key.
+        key: KeyCode,
+        /// Modifiers held at the time.
+        mods: KeyMods,
+        /// `true` if this is a repeat of a held key.
+        repeat: bool,
+    },
+    /// A key has been released.
+    KeyUp {
+        /// The key.
+        key: KeyCode,
+        /// Modifiers held at the time.
+        mods: KeyMods,
+    },
+    /// A character has been typed.
+    Char {
+        /// The character.
+        character: char,
+        /// Modifiers held at the time.
+        mods: KeyMods,
+        /// `true` if this is a repeat of a held key.
+        repeat: bool,
+    },
+    /// A mouse button has been pressed.
+    MouseDown {
+        /// The button.
+        button: MouseButton,
+        /// The x coordinate of the mouse.
+        x: f32,
+        /// The y coordinate of the mouse.
+        y: f32,
+    },
+    /// A mouse button has been released.
+    MouseUp {
+        /// The button.
+        button: MouseButton,
+        /// The x coordinate of the mouse.
+        x: f32,
+        /// The y coordinate of the mouse.
+        y: f32,
+    },
+    /// The mouse has moved.
+    MouseMove {
+        /// The new x coordinate of the mouse.
+        x: f32,
+        /// The new y coordinate of the mouse.
+        y: f32,
+    },
+    /// The mouse wheel has been scrolled.
+    Wheel {
+        /// Horizontal scroll amount.
+        x: f32,
+        /// Vertical scroll amount.
+        y: f32,
+    },
+}
+
+/// Input state of a single gamepad.
+#[cfg(feature = "gamepad")]
+#[derive(Default)]
+struct GamepadState {
+    buttons: FxHashMap<GamepadButton, InputState>,
+    axes: FxHashMap<GamepadAxis, f32>,
+}
+
+/// A post-processing shader file that is reloaded when it changes.
+#[cfg(not(target_arch = "wasm32"))]
+struct ShaderWatch {
+    path: PathBuf,
+    uniforms: Vec<UniformDesc>,
+    modified: Option<std::time::SystemTime>,
+    /// Why the last reload failed.
+    error: Option<String>,
+}
+
+/// Turn [`InputState::Pressed`] into [`InputState::Down`] and remove [`InputState::Released`] buttons.
+fn advance_input_states<K>(states: &mut FxHashMap<K, InputState>) {
+    states.retain(|_, state| match state {
+        InputState::Down => true,
+        InputState::Pressed => {
+            *state = InputState::Down;
+            true
+        }
+        InputState::Released => false,
+    });
+}
+
+/// Turn every [`InputState::Pressed`] and [`InputState::Down`] button into [`InputState::Released`].
+fn release_input_states<K>(states: &mut FxHashMap<K, InputState>) {
+    for state in states.values_mut() {
+        *state = InputState::Released;
+    }
+}
+
+/// Uploads the post shader's uniform values.
+type ApplyUniforms = Box<dyn Fn(&mut dyn RenderingBackend)>;
+
+/// The GPU resources used to draw the framebuffer to the window.
+struct Gpu {
     backend: Box<dyn RenderingBackend>,
 
+    shader: ShaderId,
     pipeline: Pipeline,
     bindings: Bindings,
+}
+
+impl Gpu {
+    #[inline]
+    fn texture(&self) -> TextureId {
+        self.bindings.images[0]
+    }
+
+    #[inline]
+    fn set_texture(&mut self, tex: TextureId) {
+        self.bindings.images[0] = tex;
+    }
+}
+
+/// An object that holds the app's global state.
+pub struct Context {
+    /// `None` for a headless context.
+    gpu: Option<Gpu>,
+    /// The window size a headless context was resized to, otherwise its display follows the framebuffer.
+    headless_screen_size: Option<(f32, f32)>,
+
+    post_uniforms_size: usize,
+    post_uniforms: Option<ApplyUniforms>,
+    #[cfg(not(target_arch = "wasm32"))]
+    shader_watch: Option<ShaderWatch>,
+    scale_mode: ScaleMode,
+    viewport: Option<(i32, i32, u32, u32)>,
 
     instant: f64,
     delta_time: f64,
+    elapsed: f64,
+    fixed_timestep: Option<f64>,
+    fixed_time_accumulator: f64,
+    target_fps: Option<u32>,
+    delta_override: Option<f64>,
+    max_delta: Option<f64>,
+    next_frame_time: f64,
+    fps: f32,
+    fps_smoothing: f32,
+    frame_count: u64,
+    time_paused: bool,
 
     clear_color: RGBA8,
+    auto_clear: bool,
     framebuffer: Vec<RGBA8>,
     buf_width: u32,
     buf_height: u32,
+    clip: Option<(i32, i32, u32, u32)>,
+    blend_mode: BlendMode,
+    framebuffer_scale: Option<u32>,
+    blur_scratch: Vec<RGBA8>,
+    texture_filter: FilterMode,
+    pixel_format: PixelFormat,
+    upload_buffer: Vec<u8>,
+    fullscreen: bool,
+    layers: Vec<Canvas>,
+    active_layer: Option<LayerId>,
+    text_origin: (i32, i32),
+    text_cursor: (i32, i32),
+    text_color: RGBA8,
 
     keys: FxHashMap<KeyCode, InputState>,
+    last_key_pressed: Option<KeyCode>,
     key_mods: KeyMods,
     mouse_pos: (f32, f32),
+    prev_mouse_pos: Option<(f32, f32)>,
+    mouse_delta: (f32, f32),
     mouse_wheel: (f32, f32),
     mouse_buttons: FxHashMap<MouseButton, InputState>,
+    last_mouse_button_pressed: Option<MouseButton>,
+    mouse_last_presses: FxHashMap<MouseButton, (f64, (f32, f32))>,
+    mouse_press_positions: FxHashMap<MouseButton, (f32, f32)>,
+    mouse_double_clicks: FxHashSet<MouseButton>,
+    double_click_threshold: f64,
+    touches: FxHashMap<u64, (f32, f32)>,
+    action_map: ActionMap,
+    events: Vec<InputEvent>,
+
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "gamepad")]
+    gamepads: FxHashMap<usize, GamepadState>,
 }
 
 impl Context {
     #[inline]
-    fn texture_params(width: u32, height: u32) -> TextureParams {
+    fn texture_params(
+        width: u32,
+        height: u32,
+        filter: FilterMode,
+        format: TextureFormat,
+    ) -> TextureParams {
         TextureParams {
             kind: TextureKind::Texture2D,
-            format: TextureFormat::RGBA8,
+            format,
             wrap: TextureWrap::Clamp,
-            min_filter: FilterMode::Nearest,
-            mag_filter: FilterMode::Nearest,
+            min_filter: filter,
+            mag_filter: filter,
             mipmap_filter: MipmapFilterMode::None,
             width,
             height,
@@ -145,23 +441,16 @@ impl Context {
         }
     }
 
-    fn new() -> Self {
+    fn new(fullscreen: bool) -> Self {
         let mut backend = window::new_rendering_backend();
 
         let (win_width, win_height) = window::screen_size();
         let (win_width, win_height) = (win_width as u32, win_height as u32);
 
-        #[rustfmt::skip]
-        let verices: [Vertex; 4] = [
-            Vertex { pos: Vec2::new(-1., -1.), uv: Vec2::new(0., 1.) },
-            Vertex { pos: Vec2::new( 1., -1.), uv: Vec2::new(1., 1.) },
-            Vertex { pos: Vec2::new( 1.,  1.), uv: Vec2::new(1., 0.) },
-            Vertex { pos: Vec2::new(-1.,  1.), uv: Vec2::new(0., 0.) },
-        ];
         let vertex_buffer = backend.new_buffer(
             BufferType::VertexBuffer,
-            BufferUsage::Immutable,
-            BufferSource::slice(&verices),
+            BufferUsage::Stream,
+            BufferSource::slice(&Self::quad_vertices(-1., -1., 1., 1.)),
         );
 
         let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
@@ -171,7 +460,12 @@ impl Context {
             BufferSource::slice(&indices),
         );
 
-        let texture = backend.new_render_texture(Self::texture_params(win_width, win_height));
+        let texture = backend.new_render_texture(Self::texture_params(
+            win_width,
+            win_height,
+            FilterMode::Nearest,
+            TextureFormat::RGBA8,
+        ));
 
         let bindings = Bindings {
             vertex_buffers: vec![vertex_buffer],
@@ -179,51 +473,96 @@ impl Context {
             images: vec![texture],
         };
 
-        let shader_meta = ShaderMeta {
-            images: vec!["tex".to_string()],
-            uniforms: UniformBlockLayout { uniforms: vec![] },
-        };
+        let source = Self::default_shader_source(backend.info().backend);
+        let (shader, pipeline) =
+            Self::new_pipeline(&mut *backend, source, vec![]).unwrap_or_else(|err| panic!("{err}"));
 
-        let shader = backend
-            .new_shader(
-                match backend.info().backend {
-                    Backend::OpenGl => ShaderSource::Glsl {
-                        vertex: SHADER_VERT,
-                        fragment: SHADER_FRAG,
-                    },
-                    Backend::Metal => ShaderSource::Msl {
-                        program: SHADER_METAL,
-                    },
-                },
-                shader_meta,
-            )
-            .unwrap_or_else(|err| panic!("{err}"));
+        let gpu = Gpu {
+            backend,
 
-        let pipeline = backend.new_pipeline(
-            &[BufferLayout::default()],
-            &[
-                VertexAttribute::new("pos", VertexFormat::Float2),
-                VertexAttribute::new("uv", VertexFormat::Float2),
-            ],
             shader,
-            PipelineParams::default(),
-        );
+            pipeline,
+            bindings,
+        };
+
+        Self::with_gpu(Some(gpu), win_width, win_height, fullscreen)
+    }
+
+    /// Create a context without a window or a GPU, e.g. for testing or benchmarking drawing code.
+    ///
+    /// The framebuffer is `width`×`height` and filled with black.
+    /// Everything that only touches the framebuffer works as usual, and the display size is the framebuffer size.
+    /// GPU and window functions do nothing (getters like [`Context::read_screen_pixels()`] return `None`),
+    /// except for [`Context::get_rendering_backend()`] and [`Context::get_mut_rendering_backend()`], which panic.
+    ///
+    /// ```
+    /// # use simple_pixels::{color, Context, DrawTarget};
+    /// let mut ctx = Context::new_headless(16, 8);
+    /// ctx.draw_rect(2, 2, 4, 4, color::RED);
+    /// ctx.draw_line(0, 7, 15, 7, color::WHITE);
+    ///
+    /// assert_eq!(ctx.get_pixel(2, 2), Some(color::RED));
+    /// assert_eq!(ctx.get_pixel(6, 2), Some(color::BLACK));
+    /// assert_eq!(ctx.get_pixel(15, 7), Some(color::WHITE));
+    ///
+    /// ctx.set_framebuffer_size(4, 4);
+    /// assert_eq!((ctx.buffer_width(), ctx.display_width()), (4, 4.));
+    /// ```
+    #[cfg(any(test, feature = "headless"))]
+    pub fn new_headless(width: u32, height: u32) -> Self {
+        Self::with_gpu(None, width, height, false)
+    }
+
+    fn with_gpu(gpu: Option<Gpu>, width: u32, height: u32, fullscreen: bool) -> Self {
+        #[cfg(feature = "gamepad")]
+        let gilrs = gilrs::Gilrs::new().ok();
 
         Self {
-            backend,
+            gpu,
+            headless_screen_size: None,
 
-            pipeline,
-            bindings,
+            post_uniforms_size: 0,
+            post_uniforms: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            shader_watch: None,
+            scale_mode: ScaleMode::Stretch,
+            viewport: None,
 
             instant: miniquad::date::now(),
             delta_time: 0.,
+            elapsed: 0.,
+            fixed_timestep: None,
+            fixed_time_accumulator: 0.,
+            target_fps: None,
+            delta_override: None,
+            max_delta: None,
+            next_frame_time: 0.,
+            fps: 0.,
+            fps_smoothing: 0.1,
+            frame_count: 0,
+            time_paused: false,
 
             clear_color: RGBA8::new(0, 0, 0, 255),
-            framebuffer: vec![RGBA8::new(0, 0, 0, 255); (win_width * win_height) as usize],
-            buf_width: win_width,
-            buf_height: win_height,
+            auto_clear: false,
+            framebuffer: vec![RGBA8::new(0, 0, 0, 255); width as usize * height as usize],
+            buf_width: width,
+            buf_height: height,
+            clip: None,
+            blend_mode: BlendMode::Replace,
+            framebuffer_scale: None,
+            blur_scratch: Vec::new(),
+            texture_filter: FilterMode::Nearest,
+            pixel_format: PixelFormat::Rgba8,
+            upload_buffer: Vec::new(),
+            fullscreen,
+            layers: Vec::new(),
+            active_layer: None,
+            text_origin: (0, 0),
+            text_cursor: (0, 0),
+            text_color: RGBA8::new(255, 255, 255, 255),
 
             keys: FxHashMap::default(),
+            last_key_pressed: None,
             key_mods: KeyMods {
                 shift: false,
                 ctrl: false,
@@ -231,19 +570,211 @@ impl Context {
                 logo: false,
             },
             mouse_pos: (0., 0.),
+            prev_mouse_pos: None,
+            mouse_delta: (0., 0.),
             mouse_wheel: (0., 0.),
             mouse_buttons: FxHashMap::default(),
+            last_mouse_button_pressed: None,
+            mouse_last_presses: FxHashMap::default(),
+            mouse_press_positions: FxHashMap::default(),
+            mouse_double_clicks: FxHashSet::default(),
+            double_click_threshold: 0.4,
+            touches: FxHashMap::default(),
+            action_map: ActionMap::new(),
+            events: Vec::new(),
+
+            #[cfg(feature = "gamepad")]
+            gamepads: gilrs
+                .iter()
+                .flat_map(|gilrs| gilrs.gamepads())
+                .map(|(id, _)| (id.into(), GamepadState::default()))
+                .collect(),
+            #[cfg(feature = "gamepad")]
+            gilrs,
+        }
+    }
+
+    /// The shader that draws the framebuffer as is.
+    fn default_shader_source(backend: Backend) -> ShaderSource<'static> {
+        match backend {
+            Backend::OpenGl => ShaderSource::Glsl {
+                vertex: SHADER_VERT,
+                fragment: SHADER_FRAG,
+            },
+            Backend::Metal => ShaderSource::Msl {
+                program: SHADER_METAL_FRAG,
+            },
         }
     }
 
+    /// Create the pipeline drawing the framebuffer to the screen.
+    ///
+    /// Only the fragment shader is taken from `source` (for Metal, `program` only contains the fragment function),
+    /// the vertex shader is always the built-in one.
+    fn new_pipeline(
+        backend: &mut dyn RenderingBackend,
+        source: ShaderSource,
+        uniforms: Vec<UniformDesc>,
+    ) -> Result<(ShaderId, Pipeline), ShaderError> {
+        let shader_meta = ShaderMeta {
+            images: vec!["tex".to_string()],
+            uniforms: UniformBlockLayout { uniforms },
+        };
+
+        let program;
+
+        let shader = backend.new_shader(
+            match source {
+                ShaderSource::Glsl { fragment, .. } => ShaderSource::Glsl {
+                    vertex: SHADER_VERT,
+                    fragment,
+                },
+                ShaderSource::Msl { program: fragment } => {
+                    program = format!("{SHADER_METAL_VERT}{fragment}");
+                    ShaderSource::Msl { program: &program }
+                }
+            },
+            shader_meta,
+        )?;
+
+        let pipeline = backend.new_pipeline(
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("uv", VertexFormat::Float2),
+            ],
+            shader,
+            PipelineParams::default(),
+        );
+
+        Ok((shader, pipeline))
+    }
+
     #[inline]
-    fn texture(&self) -> TextureId {
-        self.bindings.images[0]
+    fn quad_vertices(left: f32, bottom: f32, right: f32, top: f32) -> [Vertex; 4] {
+        #[rustfmt::skip]
+        let vertices = [
+            Vertex { pos: Vec2::new(left,  bottom), uv: Vec2::new(0., 1.) },
+            Vertex { pos: Vec2::new(right, bottom), uv: Vec2::new(1., 1.) },
+            Vertex { pos: Vec2::new(right, top),    uv: Vec2::new(1., 0.) },
+            Vertex { pos: Vec2::new(left,  top),    uv: Vec2::new(0., 0.) },
+        ];
+
+        vertices
     }
 
+    /// Returns `false` for a headless context, which has neither a window nor a GPU.
     #[inline]
-    fn set_texture(&mut self, tex: TextureId) {
-        self.bindings.images[0] = tex;
+    fn has_window(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// The window size (in screen pixels), or the framebuffer size for a headless context.
+    #[inline]
+    fn screen_size(&self) -> (f32, f32) {
+        if self.has_window() {
+            window::screen_size()
+        } else {
+            self.headless_screen_size
+                .unwrap_or((self.buf_width as f32, self.buf_height as f32))
+        }
+    }
+
+    /// The area of the window the framebuffer is drawn to as `(x, y, width, height)` (in screen pixels, origin at the top left).
+    fn output_rect(&self) -> (f32, f32, f32, f32) {
+        let (area_x, area_y, area_width, area_height) = match self.viewport {
+            Some((x, y, width, height)) => (x as f32, y as f32, width as f32, height as f32),
+            None => {
+                let (win_width, win_height) = self.screen_size();
+                (0., 0., win_width, win_height)
+            }
+        };
+        let (buf_width, buf_height) = (self.buf_width as f32, self.buf_height as f32);
+
+        let scale = (area_width / buf_width).min(area_height / buf_height);
+        let scale = match self.scale_mode {
+            ScaleMode::Stretch => return (area_x, area_y, area_width, area_height),
+            ScaleMode::Fit => scale,
+            ScaleMode::IntegerFit if scale >= 1. => scale.floor(),
+            ScaleMode::IntegerFit => scale,
+        };
+
+        let (width, height) = (buf_width * scale, buf_height * scale);
+
+        // keep the quad aligned to whole screen pixels
+        (
+            area_x + ((area_width - width) / 2.).floor(),
+            area_y + ((area_height - height) / 2.).floor(),
+            width,
+            height,
+        )
+    }
+
+    /// Convert a position in the window (in screen coords) to framebuffer pixels.
+    ///
+    /// Accounts for the current [`ScaleMode`] and viewport, the result may be outside the framebuffer.
+    fn screen_to_framebuffer(&self, x: f32, y: f32) -> (f32, f32) {
+        let (out_x, out_y, out_width, out_height) = self.output_rect();
+
+        (
+            (x - out_x) / out_width * self.buf_width as f32,
+            (y - out_y) / out_height * self.buf_height as f32,
+        )
+    }
+
+    /// Release all held keys and mouse buttons.
+    ///
+    /// Called when the window loses focus, since the key up events won't be delivered to it then.
+    /// Gamepads are polled independently of the window, so their buttons keep their state.
+    fn release_all_input(&mut self) {
+        release_input_states(&mut self.keys);
+        release_input_states(&mut self.mouse_buttons);
+        self.mouse_press_positions.clear();
+        self.key_mods = KeyMods {
+            shift: false,
+            ctrl: false,
+            alt: false,
+            logo: false,
+        };
+    }
+
+    /// Move the input state to the next frame: pressed buttons become held, released ones are forgotten.
+    ///
+    /// Done once per frame after `draw()`, so `update()` and `draw()` see the same input state.
+    fn end_frame_input(&mut self) {
+        self.mouse_wheel = (0., 0.);
+
+        advance_input_states(&mut self.keys);
+        advance_input_states(&mut self.mouse_buttons);
+        self.last_key_pressed = None;
+        self.last_mouse_button_pressed = None;
+        self.events.clear();
+        self.mouse_double_clicks.clear();
+
+        #[cfg(feature = "gamepad")]
+        for gamepad in self.gamepads.values_mut() {
+            advance_input_states(&mut gamepad.buttons);
+        }
+    }
+
+    /// Upload the framebuffer quad matching the current scale mode, framebuffer and window size.
+    fn update_quad(&mut self) {
+        let (win_width, win_height) = self.screen_size();
+        let (x, y, width, height) = self.output_rect();
+
+        let vertices = Self::quad_vertices(
+            x / win_width * 2. - 1.,
+            1. - (y + height) / win_height * 2.,
+            (x + width) / win_width * 2. - 1.,
+            1. - y / win_height * 2.,
+        );
+
+        if let Some(gpu) = &mut self.gpu {
+            gpu.backend.buffer_update(
+                gpu.bindings.vertex_buffers[0],
+                BufferSource::slice(&vertices),
+            );
+        }
     }
 
     /// Load file from the filesystem (desktop) or do an HTTP request (web).
@@ -308,7 +839,7 @@ impl Context {
     /// Accounts for dpi scale.
     #[inline]
     pub fn display_width(&self) -> f32 {
-        window::screen_size().0
+        self.screen_size().0
     }
 
     /// Display height (in screen coordinates).
@@ -316,19 +847,25 @@ impl Context {
     /// Accounts for dpi scale.
     #[inline]
     pub fn display_height(&self) -> f32 {
-        window::screen_size().1
+        self.screen_size().1
     }
 
-    /// Framebuffer width (in pixels).
+    /// Width (in pixels) of the active layer or the framebuffer if there isn't one.
     #[inline]
     pub fn buffer_width(&self) -> u32 {
-        self.buf_width
+        match self.active_layer {
+            Some(id) => self.layers[id.0].width(),
+            None => self.buf_width,
+        }
     }
 
-    /// Framebuffer height (in pixels).
+    /// Height (in pixels) of the active layer or the framebuffer if there isn't one.
     #[inline]
     pub fn buffer_height(&self) -> u32 {
-        self.buf_height
+        match self.active_layer {
+            Some(id) => self.layers[id.0].height(),
+            None => self.buf_height,
+        }
     }
 
     /// The dpi scaling factor (screen coords to framebuffer pixels).
@@ -337,7 +874,11 @@ impl Context {
     /// Always 1.0 if `high_dpi` in `Config` is set to `false`.
     #[inline]
     pub fn dpi_scale(&self) -> f32 {
-        window::dpi_scale()
+        if self.has_window() {
+            window::dpi_scale()
+        } else {
+            1.
+        }
     }
 
     /// Time passed between previous and current frame (in seconds).
@@ -352,415 +893,2927 @@ impl Context {
         Duration::from_secs_f64(self.delta_time)
     }
 
-    /// Set clear/background color.
+    /// Time passed since the application has started (in seconds).
     ///
-    /// The framebuffer isn't cleared automatically, use [`Context::clear()`] for that.
+    /// Only updated once per frame, so it stays the same during the whole frame.
     #[inline]
-    pub fn clear_color(&mut self, color: RGBA8) {
-        self.clear_color = color;
+    pub fn elapsed_secs(&self) -> f64 {
+        self.elapsed
     }
 
-    /// Returns current input state of a key or `None` if it isn't held.
+    /// Time passed since the application has started (as [`std::time::Duration`]).
     ///
-    /// Note that [`InputState::Released`] means that the key has **just** been released, **not** that it isn't held.
+    /// Only updated once per frame, so it stays the same during the whole frame.
     #[inline]
-    pub fn get_key_state(&self, key: KeyCode) -> Option<InputState> {
-        self.keys.get(&key).copied()
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed)
     }
 
-    /// Returns all keys that are down or have just been pressed/released.
+    /// Stop the clock: until [`Context::resume_time()`] is called, the delta time is zero and the elapsed time doesn't advance.
+    ///
+    /// `update()` and `draw()` are still called every frame, `fixed_update()` isn't.
     #[inline]
-    pub fn get_all_keys(&self) -> &FxHashMap<KeyCode, InputState> {
-        &self.keys
+    pub fn pause_time(&mut self) {
+        self.time_paused = true;
     }
 
-    /// Returns `true` if a key is down.
+    /// Resume the clock stopped by [`Context::pause_time()`].
+    ///
+    /// The time spent paused is skipped, it doesn't show up in the next delta time.
     #[inline]
-    pub fn is_key_down(&self, key: KeyCode) -> bool {
-        self.get_key_state(key)
-            .map_or(false, |state| state != InputState::Released)
+    pub fn resume_time(&mut self) {
+        self.time_paused = false;
     }
 
-    /// Returns `true` if a key has just been pressed.
+    /// Returns `true` if the clock is stopped by [`Context::pause_time()`].
     #[inline]
-    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
-        self.get_key_state(key)
-            .map_or(false, |state| state == InputState::Pressed)
+    pub fn is_time_paused(&self) -> bool {
+        self.time_paused
     }
 
-    /// Returns `true` if a key has just been released.
+    /// Smoothed frames per second estimate (an exponential moving average of `1 / delta_time`).
     #[inline]
-    pub fn is_key_released(&self, key: KeyCode) -> bool {
-        self.get_key_state(key)
-            .map_or(false, |state| state == InputState::Released)
+    pub fn fps(&self) -> f32 {
+        self.fps
     }
 
-    /// Returns currently held key modifiers.
+    /// Set the smoothing factor for [`Context::fps()`] (clamped to `0.0..=1.0`).
+    ///
+    /// Higher values make the estimate react to changes faster, `1.0` disables smoothing. The default is `0.1`.
     #[inline]
-    pub fn get_key_mods(&self) -> KeyMods {
-        self.key_mods
+    pub fn set_fps_smoothing(&mut self, alpha: f32) {
+        self.fps_smoothing = alpha.clamp(0., 1.);
     }
 
-    /// Returns current mouse position in the window (in screen coords).
+    /// Number of frames since the application has started (including the current one).
     #[inline]
-    pub fn get_screen_mouse_pos(&self) -> (f32, f32) {
-        self.mouse_pos
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
     }
 
-    /// Returns current mouse position in the window (in framebuffer pixels).
+    /// Set the time step for [`App::fixed_update()`] or disable fixed updates with `None`.
+    ///
+    /// Disabled by default. A zero time step also disables fixed updates.
     #[inline]
-    pub fn get_framebuffer_mouse_pos(&self) -> (i32, i32) {
-        let (x, y) = self.mouse_pos;
-        let (win_width, win_height) = window::screen_size();
-
-        (
-            (x / win_width * self.buf_width as f32) as _,
-            (y / win_height * self.buf_height as f32) as _,
-        )
+    pub fn set_fixed_timestep(&mut self, dt: Option<Duration>) {
+        self.fixed_timestep = dt.map(|dt| dt.as_secs_f64()).filter(|&dt| dt > 0.);
+        self.fixed_time_accumulator = 0.;
     }
 
-    /// Get current mouse wheel movement.
+    /// Returns the time step for [`App::fixed_update()`] or `None` if fixed updates are disabled.
     #[inline]
-    pub fn get_mouse_wheel(&self) -> (f32, f32) {
-        self.mouse_wheel
+    pub fn get_fixed_timestep(&self) -> Option<Duration> {
+        self.fixed_timestep.map(Duration::from_secs_f64)
     }
 
-    /// Returns current input state of a mouse button or `None` if it isn't held.
+    /// Returns how far the time is between the last [`App::fixed_update()`] and the next one, from 0.0 to 1.0.
     ///
-    /// Note that [`InputState::Released`] means that the key has **just** been released, **not** that it isn't held.
+    /// Can be used in [`App::draw()`] to interpolate between the previous and the current state of the simulation
+    /// for smooth motion even when the frame rate doesn't match the fixed time step:
+    /// keep both states around and draw `previous + (current - previous) * alpha`.
+    /// Always 1.0 (i.e. the current state) if fixed updates are disabled.
+    ///
+    /// ```
+    /// # #[cfg(feature = "headless")] {
+    /// # use simple_pixels::Context;
+    /// # use std::time::Duration;
+    /// let mut ctx = Context::new_headless(1, 1);
+    /// assert_eq!(ctx.get_interpolation_alpha(), 1.);
+    ///
+    /// ctx.set_fixed_timestep(Some(Duration::from_millis(10)));
+    /// assert_eq!(ctx.get_interpolation_alpha(), 0.);
+    /// # }
+    /// ```
     #[inline]
-    pub fn get_mouse_button_state(&self, button: MouseButton) -> Option<InputState> {
-        self.mouse_buttons.get(&button).copied()
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        match self.fixed_timestep {
+            Some(timestep) => (self.fixed_time_accumulator / timestep).clamp(0., 1.) as f32,
+            None => 1.,
+        }
     }
 
-    /// Returns all mouse buttons that are down or have just been pressed/released.
+    /// Limit the delta time of a frame to `max` or remove the limit with `None`.
+    ///
+    /// Prevents huge time steps (e.g. after the window has been unfocused or the app has been paused in a debugger)
+    /// from breaking simulations. Unlike [`Context::set_fixed_timestep()`], this only cuts off spikes,
+    /// the time lost this way isn't made up for later.
+    /// Doesn't affect [`Context::set_fixed_delta_override()`]. No limit by default.
     #[inline]
-    pub fn get_all_mouse_buttons(&self) -> &FxHashMap<MouseButton, InputState> {
-        &self.mouse_buttons
+    pub fn set_max_delta(&mut self, max: Option<Duration>) {
+        self.max_delta = max.map(|max| max.as_secs_f64());
     }
 
-    /// Returns `true` if a mouse button is down.
+    /// Returns the delta time limit or `None` if there's no limit.
     #[inline]
-    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
-        self.get_mouse_button_state(button)
-            .map_or(false, |state| state != InputState::Released)
+    pub fn get_max_delta(&self) -> Option<Duration> {
+        self.max_delta.map(Duration::from_secs_f64)
     }
 
-    /// Returns `true` if a mouse button has just been pressed.
+    /// Make every frame report `dt` seconds as its delta time instead of the measured time, or go back to measuring it with `None`.
+    ///
+    /// Unlike [`Context::set_fixed_timestep()`], this changes [`Context::delta_time_secs()`] (and everything based on it,
+    /// including fixed updates and [`Context::elapsed_secs()`]) regardless of how much time has really passed,
+    /// which makes replays of recorded input reproducible.
+    /// Paused time still has zero delta time. Negative and non-finite values are treated as `None`.
     #[inline]
-    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
-        self.get_mouse_button_state(button)
-            .map_or(false, |state| state == InputState::Pressed)
+    pub fn set_fixed_delta_override(&mut self, dt: Option<f64>) {
+        self.delta_override = dt.filter(|dt| dt.is_finite() && *dt >= 0.);
     }
 
-    /// Returns `true` if a mouse button has just been released.
+    /// Returns the delta time override in seconds or `None` if the delta time is measured.
     #[inline]
-    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
-        self.get_mouse_button_state(button)
-            .map_or(false, |state| state == InputState::Released)
+    pub fn get_fixed_delta_override(&self) -> Option<f64> {
+        self.delta_override
     }
 
-    /// Quit the application.
+    /// Limit the frame rate by sleeping at the end of every frame or disable the limit with `None`.
+    ///
+    /// Useful when vsync is off or unavailable, so that the application doesn't use the CPU at 100%.
+    /// This is a coarse cap and not a precise limiter: it relies on [`std::thread::sleep`], which may oversleep.
+    /// The sleep time is based on when the frames were supposed to end, so the average frame rate stays close to the target.
+    /// Does nothing on WASM. Disabled by default. A zero frame rate also disables the limit.
     #[inline]
-    pub fn quit(&self) {
-        window::request_quit();
+    pub fn set_target_fps(&mut self, fps: Option<u32>) {
+        self.target_fps = fps.filter(|&fps| fps > 0);
+        self.next_frame_time = miniquad::date::now();
     }
 
-    /// Show or hide the mouse cursor.
+    /// Returns the frame rate limit or `None` if the frame rate isn't limited.
     #[inline]
-    pub fn show_mouse(&self, shown: bool) {
-        window::show_mouse(shown);
+    pub fn get_target_fps(&self) -> Option<u32> {
+        self.target_fps
     }
 
-    /// Show or hide onscreen keyboard. This only works on Android.
-    #[inline]
-    pub fn show_keyboard(&self, shown: bool) {
-        window::show_keyboard(shown);
+    /// Sleep for the rest of the frame if the frame rate is limited.
+    fn wait_for_next_frame(&mut self) {
+        let Some(fps) = self.target_fps else {
+            return;
+        };
+
+        let now = miniquad::date::now();
+        let frame_time = 1. / fps as f64;
+
+        self.next_frame_time += frame_time;
+
+        // don't try to catch up after a long frame, that would make the next frames run uncapped
+        if self.next_frame_time < now - frame_time {
+            self.next_frame_time = now;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.next_frame_time > now {
+            std::thread::sleep(Duration::from_secs_f64(self.next_frame_time - now));
+        }
     }
 
-    /// Set the mouse cursor icon.
+    /// Set clear/background color.
+    ///
+    /// The framebuffer isn't cleared automatically, use [`Context::clear()`] for that.
     #[inline]
-    pub fn set_mouse_cursor(&self, cursor_icon: CursorIcon) {
-        window::set_mouse_cursor(cursor_icon);
+    pub fn clear_color(&mut self, color: RGBA8) {
+        self.clear_color = color;
     }
 
-    /// Set window to fullscreen or not.
+    /// Returns current clear/background color.
     #[inline]
-    pub fn set_fullscreen(&self, fullscreen: bool) {
-        window::set_fullscreen(fullscreen);
+    pub fn get_clear_color(&self) -> RGBA8 {
+        self.clear_color
     }
 
-    /// Get current OS clipboard value.
+    /// Clear the framebuffer with the current [`Context::clear_color()`] at the start of every frame
+    /// (before [`App::fixed_update()`] and [`App::update()`]).
+    ///
+    /// Disabled by default, so whatever was drawn stays until it's drawn over.
+    /// Layers aren't cleared automatically, even the active one.
     #[inline]
-    pub fn get_clipboard(&self) -> Option<String> {
-        window::clipboard_get()
+    pub fn set_auto_clear(&mut self, enabled: bool) {
+        self.auto_clear = enabled;
     }
 
-    /// Save value to OS clipboard.
+    /// Returns `true` if the framebuffer is cleared at the start of every frame.
     #[inline]
-    pub fn set_clipboard(&self, data: impl AsRef<str>) {
-        window::clipboard_set(data.as_ref());
+    pub fn is_auto_clear(&self) -> bool {
+        self.auto_clear
     }
 
-    /// Set the application's window size.
+    /// Returns current input state of a key or `None` if it isn't held.
     ///
-    /// Note: resizing the window does not resize the framebuffer.
-    /// It will be scaled to the whole window.
-    /// You can use [`Context::set_framebuffer_size()`] for resizing the framebuffer.
+    /// Note that [`InputState::Released`] means that the key has **just** been released, **not** that it isn't held.
     #[inline]
-    pub fn set_window_size(&mut self, new_width: u32, new_height: u32) {
-        window::set_window_size(new_width, new_height);
+    pub fn get_key_state(&self, key: KeyCode) -> Option<InputState> {
+        self.keys.get(&key).copied()
     }
 
-    /// Set the framebuffer size. The buffer will be cleared.
-    ///
-    /// This doesn't change the window size.
-    /// The framebuffer will be scaled to the whole window.
-    pub fn set_framebuffer_size(&mut self, new_width: u32, new_height: u32) {
-        // miniquad's `texture_resize` is currently unimplemented on Metal backend so we're doing this awkward dance
-
-        self.backend.delete_texture(self.texture());
+    /// Returns all keys that are down or have just been pressed/released.
+    #[inline]
+    pub fn get_all_keys(&self) -> &FxHashMap<KeyCode, InputState> {
+        &self.keys
+    }
 
-        let new_texture = self
-            .backend
-            .new_render_texture(Self::texture_params(new_width, new_height));
-        self.set_texture(new_texture);
+    /// Returns `true` if a key is down.
+    #[inline]
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        matches!(
+            self.get_key_state(key),
+            Some(InputState::Pressed | InputState::Down)
+        )
+    }
 
-        self.buf_width = new_width;
-        self.buf_height = new_height;
+    /// Returns `true` if a key has just been pressed.
+    #[inline]
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.get_key_state(key) == Some(InputState::Pressed)
+    }
 
-        self.framebuffer.fill(self.clear_color);
-        self.framebuffer
-            .resize((new_width * new_height) as usize, self.clear_color);
+    /// Returns `true` if a key has just been pressed while exactly the modifiers in `mods` are held, e.g. for Ctrl+S.
+    ///
+    /// See [`Context::is_key_pressed_with_any()`] for a variant that allows extra modifiers.
+    #[inline]
+    pub fn is_key_pressed_with(&self, key: KeyCode, mods: KeyMods) -> bool {
+        self.is_key_pressed(key) && self.key_mods == mods
     }
 
-    /// Clear the screen framebuffer with the current [`Context::clear_color()`].
+    /// Returns `true` if a key has just been pressed while (at least) the modifiers in `mods` are held.
+    ///
+    /// Other modifiers may be held too, e.g. Ctrl+Shift+S counts as Ctrl+S.
     #[inline]
-    pub fn clear(&mut self) {
-        for pix in self.framebuffer.iter_mut() {
-            *pix = self.clear_color;
-        }
+    pub fn is_key_pressed_with_any(&self, key: KeyCode, mods: KeyMods) -> bool {
+        let held = self.key_mods;
+
+        self.is_key_pressed(key)
+            && (held.shift || !mods.shift)
+            && (held.ctrl || !mods.ctrl)
+            && (held.alt || !mods.alt)
+            && (held.logo || !mods.logo)
     }
 
-    /// Draw a pixels at (x, y).
+    /// Returns `true` if a key has just been released.
+    #[inline]
+    pub fn is_key_released(&self, key: KeyCode) -> bool {
+        self.get_key_state(key) == Some(InputState::Released)
+    }
+
+    /// Returns the key that has just been pressed (the last one if there were several) or `None` if there wasn't any.
     ///
-    /// Does nothing if the position is outside the screen.
+    /// Useful for key rebinding screens.
     #[inline]
-    pub fn draw_pixel(&mut self, x: i32, y: i32, color: RGBA8) {
-        if let Some(pix) = self
-            .framebuffer
-            .get_mut(y as usize * self.buf_width as usize + x as usize)
-        {
-            *pix = color;
-        }
+    pub fn get_last_key_pressed(&self) -> Option<KeyCode> {
+        self.last_key_pressed
     }
 
-    /// Draw a colored rectangle.
+    /// Returns currently held key modifiers.
+    #[inline]
+    pub fn get_key_mods(&self) -> KeyMods {
+        self.key_mods
+    }
+
+    /// Returns `true` if either Shift key is held.
+    #[inline]
+    pub fn is_shift_down(&self) -> bool {
+        self.key_mods.shift
+    }
+
+    /// Returns `true` if either Ctrl key is held.
+    #[inline]
+    pub fn is_ctrl_down(&self) -> bool {
+        self.key_mods.ctrl
+    }
+
+    /// Returns `true` if either Alt key is held.
+    #[inline]
+    pub fn is_alt_down(&self) -> bool {
+        self.key_mods.alt
+    }
+
+    /// Returns `true` if either logo (Windows/Command/Super) key is held.
+    #[inline]
+    pub fn is_logo_down(&self) -> bool {
+        self.key_mods.logo
+    }
+
+    /// Store the modifiers reported by an input event caused by `key` (if it was a key event).
     ///
-    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
-    pub fn draw_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: RGBA8) {
-        simple_blit::blit(
-            self.as_mut_surface()
-                .offset_surface_mut([x as u32, y as _].into()),
-            simple_blit::SingleValueSurface::new(color, [width, height].into()),
-            &[],
-        );
+    /// Some platforms report the modifiers as they were before the event,
+    /// so the state of the modifier keys themselves takes priority.
+    fn update_key_mods(&mut self, key_mods: KeyMods, key: Option<KeyCode>) {
+        let modifier = |reported: bool, left: KeyCode, right: KeyCode| {
+            self.is_key_down(left)
+                || self.is_key_down(right)
+                || (reported && key != Some(left) && key != Some(right))
+        };
+
+        self.key_mods = KeyMods {
+            shift: modifier(key_mods.shift, KeyCode::LeftShift, KeyCode::RightShift),
+            ctrl: modifier(key_mods.ctrl, KeyCode::LeftControl, KeyCode::RightControl),
+            alt: modifier(key_mods.alt, KeyCode::LeftAlt, KeyCode::RightAlt),
+            logo: modifier(key_mods.logo, KeyCode::LeftSuper, KeyCode::RightSuper),
+        };
+    }
+
+    /// Returns current mouse position in the window (in screen coords).
+    #[inline]
+    pub fn get_screen_mouse_pos(&self) -> (f32, f32) {
+        self.mouse_pos
     }
 
-    /// Fill a rectangle with provided pixels (row-major order).
+    /// Returns current mouse position in the window (in framebuffer pixels).
     ///
-    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
-    pub fn draw_pixels(&mut self, x: i32, y: i32, width: u32, height: u32, pixels: &[RGBA8]) {
-        if let Some(buffer) = simple_blit::GenericSurface::new(pixels, [width, height].into()) {
-            simple_blit::blit(
-                self.as_mut_surface()
-                    .offset_surface_mut([x as u32, y as _].into()),
-                buffer.sub_surface([0, 0].into(), [width, height].into()),
-                &[],
-            );
-        }
+    /// Accounts for the current [`ScaleMode`] and [viewport](Context::set_viewport).
+    /// The position may be outside the framebuffer (e.g. when the mouse is over the black bars),
+    /// use [`Context::get_framebuffer_mouse_pos_checked()`] if that's not desired.
+    #[inline]
+    pub fn get_framebuffer_mouse_pos(&self) -> (i32, i32) {
+        let (x, y) = self.screen_to_framebuffer(self.mouse_pos.0, self.mouse_pos.1);
+
+        (x.floor() as _, y.floor() as _)
     }
 
-    /// Fill the entire screen framebuffer at once.
+    /// Returns current mouse position in the window (in framebuffer pixels)
+    /// or `None` if the mouse isn't over the framebuffer.
     ///
-    /// Does not panic if a part of the rectangle isn't on screen, just draws the part that is.
-    pub fn draw_screen(&mut self, pixels: &[RGBA8]) {
-        if let Some(buffer) = simple_blit::GenericSurface::new(
-            pixels,
-            simple_blit::size(self.buf_width, self.buf_height),
-        ) {
-            simple_blit::blit(self.as_mut_surface(), buffer, &[]);
-        }
+    /// Accounts for the current [`ScaleMode`] and [viewport](Context::set_viewport).
+    #[inline]
+    pub fn get_framebuffer_mouse_pos_checked(&self) -> Option<(i32, i32)> {
+        let (x, y) = self.get_framebuffer_mouse_pos();
+
+        // not `draw::pixel_index()`, which would check against the active layer
+        (x >= 0 && y >= 0 && (x as u32) < self.buf_width && (y as u32) < self.buf_height)
+            .then_some((x, y))
     }
 
-    /// Returns the framebuffer's contents.
+    /// Returns the framebuffer pixel under the mouse cursor
+    /// or `None` if the mouse isn't over the framebuffer (e.g. it's over the black bars).
+    ///
+    /// Accounts for the current [`ScaleMode`] and [viewport](Context::set_viewport).
+    /// No dpi conversion is needed, since miniquad reports the mouse position in physical pixels like the display size.
     #[inline]
-    pub fn get_draw_buffer(&self) -> &[RGBA8] {
-        &self.framebuffer
+    pub fn get_mouse_pixel(&self) -> Option<(u32, u32)> {
+        self.get_framebuffer_mouse_pos_checked()
+            .map(|(x, y)| (x as u32, y as u32))
     }
 
-    /// Returns the framebuffer's contents.
+    /// Returns current mouse position in the window (in screen coords) as a [`Point`].
+    #[inline]
+    pub fn get_screen_mouse_point(&self) -> Point {
+        self.mouse_pos.into()
+    }
+
+    /// Returns current mouse position in the window (in framebuffer pixels) as an [`IPoint`]
+    /// or `None` if the mouse isn't over the framebuffer.
     ///
-    /// Can be used for drawing.
+    /// Accounts for the current [`ScaleMode`] and [viewport](Context::set_viewport).
     #[inline]
-    pub fn get_mut_draw_buffer(&mut self) -> &mut [RGBA8] {
-        &mut self.framebuffer
+    pub fn get_framebuffer_mouse_point(&self) -> Option<IPoint> {
+        self.get_framebuffer_mouse_pos_checked().map(IPoint::from)
     }
 
-    /// Get the draw framebuffer as a [`simple_blit::GenericSurface`].
+    /// Returns how much the mouse has moved since the previous frame (in screen coords).
+    ///
+    /// Always `(0.0, 0.0)` on the first frame.
     #[inline]
-    pub fn as_surface(&self) -> GenericSurface<&[RGBA8], RGBA8> {
-        GenericSurface::new(
-            &self.framebuffer[..],
-            simple_blit::size(self.buf_width, self.buf_height),
-        )
-        .unwrap()
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
     }
 
-    /// Get the draw framebuffer as a mutable [`simple_blit::GenericSurface`].
+    /// Get current mouse wheel movement.
+    ///
+    /// This is the sum of all wheel movement since the previous frame.
     #[inline]
-    pub fn as_mut_surface(&mut self) -> GenericSurface<&mut [RGBA8], RGBA8> {
-        GenericSurface::new(
-            &mut self.framebuffer[..],
-            simple_blit::size(self.buf_width, self.buf_height),
+    pub fn get_mouse_wheel(&self) -> (f32, f32) {
+        self.mouse_wheel
+    }
+
+    /// Returns current input state of a mouse button or `None` if it isn't held.
+    ///
+    /// Note that [`InputState::Released`] means that the key has **just** been released, **not** that it isn't held.
+    #[inline]
+    pub fn get_mouse_button_state(&self, button: MouseButton) -> Option<InputState> {
+        self.mouse_buttons.get(&button).copied()
+    }
+
+    /// Returns all mouse buttons that are down or have just been pressed/released.
+    #[inline]
+    pub fn get_all_mouse_buttons(&self) -> &FxHashMap<MouseButton, InputState> {
+        &self.mouse_buttons
+    }
+
+    /// Returns `true` if a mouse button is down.
+    #[inline]
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        matches!(
+            self.get_mouse_button_state(button),
+            Some(InputState::Pressed | InputState::Down)
         )
-        .unwrap()
     }
 
-    /// Set the filter for the texture that is used for rendering.
+    /// Returns `true` if a mouse button has just been pressed.
     #[inline]
-    pub fn set_texture_filter(&mut self, filter: FilterMode) {
-        self.backend
-            .texture_set_filter(self.texture(), filter, MipmapFilterMode::None);
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.get_mouse_button_state(button) == Some(InputState::Pressed)
     }
 
-    /// Get the underlying [`RenderingBackend`](https://docs.rs/miniquad/latest/miniquad/graphics/trait.RenderingBackend.html).
+    /// Returns `true` if a mouse button has just been released.
     #[inline]
-    pub fn get_rendering_backend(&self) -> &dyn RenderingBackend {
-        &*self.backend
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.get_mouse_button_state(button) == Some(InputState::Released)
     }
 
-    /// Get the underlying [`RenderingBackend`](https://docs.rs/miniquad/latest/miniquad/graphics/trait.RenderingBackend.html).
+    /// Returns how far the mouse has moved (in screen coords) since a mouse button was pressed
+    /// or `None` if the button isn't down.
     #[inline]
-    pub fn get_mut_rendering_backend(&mut self) -> &mut dyn RenderingBackend {
-        &mut *self.backend
+    pub fn get_mouse_drag(&self, button: MouseButton) -> Option<(f32, f32)> {
+        self.mouse_press_positions
+            .get(&button)
+            .map(|&(x, y)| (self.mouse_pos.0 - x, self.mouse_pos.1 - y))
     }
-}
 
-/// Application state.
-pub trait App {
-    /// Called every frame.
-    fn update(&mut self, ctx: &mut Context);
+    /// Returns the mouse button that has just been pressed (the last one if there were several)
+    /// or `None` if there wasn't any.
+    #[inline]
+    pub fn get_last_mouse_button_pressed(&self) -> Option<MouseButton> {
+        self.last_mouse_button_pressed
+    }
 
-    /// Called every frame after `update()`.
-    /// See <https://docs.rs/miniquad/latest/miniquad/trait.EventHandler.html#tymethod.update> for specifics.
-    fn draw(&mut self, ctx: &mut Context);
-}
+    /// Returns `true` if a mouse button has just been pressed for the second time in a short period of time
+    /// without moving the mouse too far.
+    ///
+    /// See [`Context::set_double_click_threshold()`].
+    #[inline]
+    pub fn is_mouse_button_double_clicked(&self, button: MouseButton) -> bool {
+        self.mouse_double_clicks.contains(&button)
+    }
 
-struct Handler<S: App> {
-    ctx: Context,
-    state: S,
-}
+    /// Set the maximum time between two presses of a mouse button that counts as a double click.
+    ///
+    /// The default is 400 milliseconds.
+    #[inline]
+    pub fn set_double_click_threshold(&mut self, threshold: Duration) {
+        self.double_click_threshold = threshold.as_secs_f64();
+    }
 
-impl<S> EventHandler for Handler<S>
-where
-    S: App,
-{
-    fn update(&mut self) {
-        let new_instant = miniquad::date::now();
-        self.ctx.delta_time = new_instant - self.ctx.instant;
-        self.ctx.instant = new_instant;
+    /// Take the keyboard and mouse events of the current frame in the order they happened.
+    ///
+    /// Unlike the input state queries, this doesn't lose the order of the events or events that cancel each other out,
+    /// e.g. two characters typed during one frame or a quick press and release of a button.
+    /// Events that aren't taken by the end of the frame are dropped.
+    #[inline]
+    pub fn drain_events(&mut self) -> impl Iterator<Item = InputEvent> + '_ {
+        self.events.drain(..)
+    }
 
-        self.state.update(&mut self.ctx);
+    /// Returns the action map used by [`Context::is_action_down()`] and similar functions.
+    #[inline]
+    pub fn action_map(&self) -> &ActionMap {
+        &self.action_map
+    }
+
+    /// Returns the action map used by [`Context::is_action_down()`] and similar functions for changing the bindings.
+    #[inline]
+    pub fn action_map_mut(&mut self) -> &mut ActionMap {
+        &mut self.action_map
+    }
 
-        self.ctx.mouse_wheel = (0., 0.);
+    /// Returns `true` if the state of any input bound to an action matches.
+    fn action_matches(&self, action: &str, matches: impl Fn(InputState) -> bool) -> bool {
+        self.action_map
+            .bindings(action)
+            .iter()
+            .filter_map(|binding| match binding {
+                Binding::Key(key) => self.get_key_state(*key),
+                Binding::MouseButton(button) => self.get_mouse_button_state(*button),
+            })
+            .any(matches)
+    }
 
-        self.ctx.keys.retain(|_, state| match state {
-            InputState::Down => true,
-            InputState::Pressed => {
-                *state = InputState::Down;
-                true
-            }
-            InputState::Released => false,
-        });
+    /// Returns `true` if any input bound to an action is down.
+    #[inline]
+    pub fn is_action_down(&self, action: &str) -> bool {
+        self.action_matches(action, |state| {
+            matches!(state, InputState::Pressed | InputState::Down)
+        })
+    }
 
-        self.ctx.mouse_buttons.retain(|_, state| match state {
-            InputState::Down => true,
-            InputState::Pressed => {
-                *state = InputState::Down;
-                true
-            }
-            InputState::Released => false,
-        });
+    /// Returns `true` if any input bound to an action has just been pressed.
+    #[inline]
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.action_matches(action, |state| state == InputState::Pressed)
     }
 
-    fn draw(&mut self) {
-        self.state.draw(&mut self.ctx);
+    /// Returns `true` if any input bound to an action has just been released.
+    #[inline]
+    pub fn is_action_released(&self, action: &str) -> bool {
+        self.action_matches(action, |state| state == InputState::Released)
+    }
 
-        self.ctx
-            .backend
-            .texture_update(self.ctx.texture(), self.ctx.framebuffer.as_bytes());
+    /// Returns the positions of the touch points that are currently down (in framebuffer pixels) by their ids.
+    ///
+    /// Accounts for the current [`ScaleMode`] and [viewport](Context::set_viewport). The positions may be outside the framebuffer.
+    #[inline]
+    pub fn active_touches(&self) -> &FxHashMap<u64, (f32, f32)> {
+        &self.touches
+    }
 
-        self.ctx.backend.begin_default_pass(PassAction::Nothing);
+    /// Returns the ids of the connected gamepads.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    pub fn get_gamepads(&self) -> impl Iterator<Item = usize> + '_ {
+        self.gamepads.keys().copied()
+    }
 
-        self.ctx.backend.apply_pipeline(&self.ctx.pipeline);
-        self.ctx.backend.apply_bindings(&self.ctx.bindings);
+    /// Returns current input state of a gamepad button or `None` if it isn't held (or the gamepad isn't connected).
+    ///
+    /// Note that [`InputState::Released`] means that the button has **just** been released, **not** that it isn't held.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    pub fn get_gamepad_button_state(&self, id: usize, button: GamepadButton) -> Option<InputState> {
+        self.gamepads.get(&id)?.buttons.get(&button).copied()
+    }
 
-        self.ctx.backend.draw(0, 6, 1);
+    /// Returns `true` if a gamepad button is down.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    pub fn is_gamepad_button_down(&self, id: usize, button: GamepadButton) -> bool {
+        matches!(
+            self.get_gamepad_button_state(id, button),
+            Some(InputState::Pressed | InputState::Down)
+        )
+    }
 
-        self.ctx.backend.end_render_pass();
+    /// Returns `true` if a gamepad button has just been pressed.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    pub fn is_gamepad_button_pressed(&self, id: usize, button: GamepadButton) -> bool {
+        self.get_gamepad_button_state(id, button) == Some(InputState::Pressed)
+    }
 
-        self.ctx.backend.commit_frame();
+    /// Returns `true` if a gamepad button has just been released.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    pub fn is_gamepad_button_released(&self, id: usize, button: GamepadButton) -> bool {
+        self.get_gamepad_button_state(id, button) == Some(InputState::Released)
     }
 
+    /// Returns current value of a gamepad axis (from `-1.0` to `1.0`).
+    ///
+    /// Returns `0.0` if the axis hasn't moved yet or the gamepad isn't connected.
+    #[cfg(feature = "gamepad")]
     #[inline]
-    fn key_down_event(&mut self, key_code: KeyCode, key_mods: KeyMods, repeat: bool) {
-        if !repeat {
-            self.ctx.keys.insert(key_code, InputState::Pressed);
+    pub fn get_gamepad_axis(&self, id: usize, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .get(&id)
+            .and_then(|gamepad| gamepad.axes.get(&axis))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Quit the application.
+    #[inline]
+    pub fn quit(&self) {
+        if self.has_window() {
+            window::request_quit();
         }
+    }
 
-        self.ctx.key_mods = key_mods;
+    /// Show or hide the mouse cursor.
+    #[inline]
+    pub fn show_mouse(&self, shown: bool) {
+        if self.has_window() {
+            window::show_mouse(shown);
+        }
     }
 
+    /// Show or hide onscreen keyboard. This only works on Android.
     #[inline]
-    fn key_up_event(&mut self, key_code: KeyCode, key_mods: KeyMods) {
-        self.ctx.keys.insert(key_code, InputState::Released);
-        self.ctx.key_mods = key_mods;
+    pub fn show_keyboard(&self, shown: bool) {
+        if self.has_window() {
+            window::show_keyboard(shown);
+        }
     }
 
+    /// Set the mouse cursor icon.
     #[inline]
-    fn mouse_button_down_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
-        self.ctx.mouse_buttons.insert(button, InputState::Pressed);
+    pub fn set_mouse_cursor(&self, cursor_icon: CursorIcon) {
+        if self.has_window() {
+            window::set_mouse_cursor(cursor_icon);
+        }
     }
 
+    /// Set window to fullscreen or not.
     #[inline]
-    fn mouse_button_up_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
-        self.ctx.mouse_buttons.insert(button, InputState::Pressed);
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+
+        if self.has_window() {
+            window::set_fullscreen(fullscreen);
+        }
     }
 
+    /// Returns `true` if the window is fullscreen.
+    ///
+    /// This is the last value requested with [`Context::set_fullscreen()`] (or in the config at startup).
+    /// On some platforms the user or the OS can change it without the app knowing, which isn't reflected here.
     #[inline]
-    fn mouse_motion_event(&mut self, x: f32, y: f32) {
-        self.ctx.mouse_pos = (x, y);
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
     }
 
+    /// Get current OS clipboard value.
     #[inline]
-    fn mouse_wheel_event(&mut self, x: f32, y: f32) {
-        self.ctx.mouse_wheel = (x, y);
+    pub fn get_clipboard(&self) -> Option<String> {
+        self.has_window().then(window::clipboard_get).flatten()
+    }
+
+    /// Save value to OS clipboard.
+    #[inline]
+    pub fn set_clipboard(&self, data: impl AsRef<str>) {
+        if self.has_window() {
+            window::clipboard_set(data.as_ref());
+        }
     }
 
+    /// Get the contents of a file dropped onto the window (see [`App::files_dropped()`]).
+    ///
+    /// `index` is the index of the path passed to [`App::files_dropped()`].
+    /// Only available on web, returns `None` on other platforms.
     #[inline]
-    fn char_event(&mut self, _character: char, key_mods: KeyMods, _repeat: bool) {
-        self.ctx.key_mods = key_mods;
+    pub fn dropped_file_bytes(&self, index: usize) -> Option<Vec<u8>> {
+        self.has_window()
+            .then(|| window::dropped_file_bytes(index))
+            .flatten()
     }
-}
 
-/// Start the application using provided config and state.
-#[inline]
-pub fn start(config: Conf, state: impl App + 'static) {
-    miniquad::start(config, move || {
-        Box::new(Handler {
-            ctx: Context::new(),
-            state,
-        })
-    })
+    /// Set the application's window size.
+    ///
+    /// Note: resizing the window does not resize the framebuffer.
+    /// It will be scaled to the window according to the current [`ScaleMode`].
+    /// You can use [`Context::set_framebuffer_size()`] for resizing the framebuffer.
+    #[inline]
+    pub fn set_window_size(&mut self, new_width: u32, new_height: u32) {
+        if self.has_window() {
+            window::set_window_size(new_width, new_height);
+        }
+    }
+
+    /// Move the window so that its top left corner is at (x, y) (in screen coords).
+    ///
+    /// Only supported on Windows, does nothing on other platforms.
+    #[inline]
+    pub fn set_window_position(&mut self, x: u32, y: u32) {
+        #[cfg(target_os = "windows")]
+        if self.has_window() {
+            window::set_window_position(x, y);
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (x, y);
+        }
+    }
+
+    /// Returns the position of the window's top left corner (in screen coords).
+    ///
+    /// Only supported on Windows, always returns `None` on other platforms.
+    #[inline]
+    pub fn get_window_position(&self) -> Option<(u32, u32)> {
+        #[cfg(target_os = "windows")]
+        {
+            self.has_window().then(window::get_window_position)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            None
+        }
+    }
+
+    /// Set the framebuffer size. The buffer will be cleared with the current [`Context::clear_color()`].
+    ///
+    /// This doesn't change the window size.
+    /// The framebuffer will be scaled to the window according to the current [`ScaleMode`].
+    ///
+    /// Disables automatic sizing set by [`Context::set_framebuffer_scale()`].
+    #[inline]
+    pub fn set_framebuffer_size(&mut self, new_width: u32, new_height: u32) {
+        self.framebuffer_scale = None;
+        self.resize_framebuffer(new_width, new_height);
+    }
+
+    /// Replace the shader that draws the framebuffer to the screen, e.g. for CRT or scanline effects.
+    ///
+    /// Only the fragment shader can be replaced:
+    /// * For OpenGL (`ShaderSource::Glsl`), `fragment` is a GLSL 100 fragment shader.
+    ///   It receives the texture coordinates in `varying lowp vec2 texcoord;`
+    ///   and the framebuffer in `uniform sampler2D tex;`. `vertex` is ignored.
+    /// * For Metal (`ShaderSource::Msl`), `program` contains just the `fragmentShader` function.
+    ///   It receives `FragData in [[stage_in]]` with the texture coordinates in `in.uv`,
+    ///   the framebuffer in `texture2d<float> tex [[texture(0)]]` and `sampler texSmplr [[sampler(0)]]`.
+    ///
+    /// `uniforms` describes the shader's uniforms, their values are set with [`Context::set_post_uniforms()`].
+    /// Check [`Context::get_rendering_backend()`] to choose the language.
+    /// The previous shader is kept if this one fails to compile.
+    ///
+    /// A scanline shader (set with `vec![UniformDesc::new("lines", UniformType::Float1)]` as `uniforms`):
+    /// ```glsl
+    /// #version 100
+    /// precision mediump float;
+    ///
+    /// varying lowp vec2 texcoord;
+    ///
+    /// uniform sampler2D tex;
+    /// uniform float lines;
+    ///
+    /// void main() {
+    ///     vec4 color = texture2D(tex, texcoord);
+    ///     float scanline = 0.75 + 0.25 * sin(texcoord.y * lines * 6.2831853);
+    ///
+    ///     gl_FragColor = vec4(color.rgb * scanline, color.a);
+    /// }
+    /// ```
+    pub fn set_post_shader(
+        &mut self,
+        source: ShaderSource,
+        uniforms: Vec<UniformDesc>,
+    ) -> Result<(), ShaderError> {
+        let uniforms_size = uniforms
+            .iter()
+            .map(|uniform| uniform.uniform_type.size() * uniform.array_count)
+            .sum();
+
+        if let Some(gpu) = &mut self.gpu {
+            let (shader, pipeline) = Self::new_pipeline(&mut *gpu.backend, source, uniforms)?;
+
+            gpu.backend.delete_pipeline(gpu.pipeline);
+            gpu.backend.delete_shader(gpu.shader);
+
+            gpu.shader = shader;
+            gpu.pipeline = pipeline;
+        }
+
+        self.post_uniforms_size = uniforms_size;
+        self.post_uniforms = None;
+
+        Ok(())
+    }
+
+    /// Load the post-processing shader from a file and reload it every time the file is modified.
+    ///
+    /// The file contains what would be passed to [`Context::set_post_shader()`] as `fragment` (GLSL) or `program` (Metal),
+    /// `uniforms` is the same as there. The uniform values set with [`Context::set_post_uniforms()`] are kept on reloads.
+    ///
+    /// The file is checked at the start of every frame. If it can't be read or the shader fails to compile,
+    /// the previous shader is kept, so a typo while editing doesn't crash the app,
+    /// and the error is available from [`Context::post_shader_error()`].
+    /// Meant for shader development, not available on web.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch_post_shader(&mut self, path: impl AsRef<Path>, uniforms: Vec<UniformDesc>) {
+        self.shader_watch = Some(ShaderWatch {
+            path: path.as_ref().to_path_buf(),
+            uniforms,
+            modified: None,
+            error: None,
+        });
+
+        self.reload_watched_shader();
+    }
+
+    /// Stop reloading the post-processing shader set with [`Context::watch_post_shader()`]. The current shader is kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
+    pub fn unwatch_post_shader(&mut self) {
+        self.shader_watch = None;
+    }
+
+    /// Returns why the last reload of the shader set with [`Context::watch_post_shader()`] failed,
+    /// or `None` if it succeeded or no shader is watched.
+    ///
+    /// The error stays until the next successful reload, the app decides how to show it.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
+    pub fn post_shader_error(&self) -> Option<&str> {
+        self.shader_watch.as_ref()?.error.as_deref()
+    }
+
+    /// Reload the watched post-processing shader if its file has been modified.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_watched_shader(&mut self) {
+        let Some(watch) = &mut self.shader_watch else {
+            return;
+        };
+
+        // the file may be briefly missing while an editor saves it, it'll be picked up on the next frame
+        let modified = std::fs::metadata(&watch.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+
+        if modified.is_none() || modified == watch.modified {
+            return;
+        }
+
+        watch.modified = modified;
+
+        let source = match std::fs::read_to_string(&watch.path) {
+            Ok(source) => source,
+            Err(err) => {
+                watch.error = Some(format!("failed to read {}: {err}", watch.path.display()));
+                return;
+            }
+        };
+
+        let Some(gpu) = &self.gpu else {
+            return;
+        };
+
+        let uniforms = watch.uniforms.clone();
+        let shader_source = match gpu.backend.info().backend {
+            Backend::OpenGl => ShaderSource::Glsl {
+                vertex: SHADER_VERT,
+                fragment: &source,
+            },
+            Backend::Metal => ShaderSource::Msl { program: &source },
+        };
+
+        // the uniform layout is the same, so the values still fit
+        let post_uniforms = self.post_uniforms.take();
+
+        let error = self
+            .set_post_shader(shader_source, uniforms)
+            .err()
+            .map(|err| err.to_string());
+
+        self.post_uniforms = post_uniforms;
+
+        if let Some(watch) = &mut self.shader_watch {
+            watch.error = error;
+        }
+    }
+
+    /// Set the uniform values of the shader set with [`Context::set_post_shader()`].
+    ///
+    /// `uniforms` must be a `#[repr(C)]` struct with the fields in the order the uniforms were described,
+    /// e.g. `struct Uniforms { time: f32, resolution: [f32; 2] }` for a `Float1` and a `Float2` uniform.
+    /// The values are uploaded every frame until they're set again or the shader is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of `T` doesn't match the size of the described uniforms.
+    pub fn set_post_uniforms<T: Copy + 'static>(&mut self, uniforms: &T) {
+        assert_eq!(
+            std::mem::size_of::<T>(),
+            self.post_uniforms_size,
+            "the uniforms don't match the post shader's uniform layout"
+        );
+
+        let uniforms = *uniforms;
+
+        self.post_uniforms = Some(Box::new(move |backend| {
+            backend.apply_uniforms(UniformsSource::table(&uniforms));
+        }));
+    }
+
+    /// Go back to the default shader that draws the framebuffer as is.
+    pub fn reset_post_shader(&mut self) {
+        let Some(gpu) = &self.gpu else {
+            self.post_uniforms_size = 0;
+            self.post_uniforms = None;
+            return;
+        };
+
+        let source = Self::default_shader_source(gpu.backend.info().backend);
+
+        self.set_post_shader(source, vec![])
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    /// Set the framebuffer size, keeping its contents.
+    ///
+    /// The part of the old contents that fits is kept at the top left corner, the rest of the buffer is filled with `fill`.
+    ///
+    /// Disables automatic sizing set by [`Context::set_framebuffer_scale()`].
+    pub fn resize_framebuffer_preserving(&mut self, new_width: u32, new_height: u32, fill: RGBA8) {
+        self.framebuffer_scale = None;
+
+        let mut framebuffer = vec![fill; new_width as usize * new_height as usize];
+        let (copy_width, copy_height) = (
+            self.buf_width.min(new_width) as usize,
+            self.buf_height.min(new_height) as usize,
+        );
+
+        if copy_width > 0 {
+            for (new_row, old_row) in framebuffer
+                .chunks_exact_mut(new_width as usize)
+                .zip(self.framebuffer.chunks_exact(self.buf_width as usize))
+                .take(copy_height)
+            {
+                new_row[..copy_width].copy_from_slice(&old_row[..copy_width]);
+            }
+        }
+
+        self.framebuffer = framebuffer;
+        self.resize_texture(new_width, new_height);
+    }
+
+    /// Set the framebuffer size to the display size divided by `factor` (at least 1x1).
+    /// The buffer will be cleared with the current [`Context::clear_color()`].
+    ///
+    /// The framebuffer is automatically resized the same way when the window is resized,
+    /// until [`Context::set_framebuffer_size()`] is called. A `factor` of 0 is treated as 1.
+    #[inline]
+    pub fn set_framebuffer_scale(&mut self, factor: u32) {
+        self.framebuffer_scale = Some(factor.max(1));
+        self.apply_framebuffer_scale();
+    }
+
+    /// Make every framebuffer pixel match a physical pixel of the display and keep it that way when the window is resized.
+    ///
+    /// With `high_dpi` enabled, the display size is already in physical pixels (see [`Context::dpi_scale()`]),
+    /// so this is the same as `set_framebuffer_scale(1)`.
+    /// The buffer will be cleared with the current [`Context::clear_color()`].
+    #[inline]
+    pub fn match_framebuffer_to_display(&mut self) {
+        self.set_framebuffer_scale(1);
+    }
+
+    fn apply_framebuffer_scale(&mut self) {
+        if let Some(factor) = self.framebuffer_scale {
+            let (win_width, win_height) = self.screen_size();
+
+            self.resize_framebuffer(
+                (win_width as u32 / factor).max(1),
+                (win_height as u32 / factor).max(1),
+            );
+        }
+    }
+
+    fn resize_framebuffer(&mut self, new_width: u32, new_height: u32) {
+        self.framebuffer.fill(self.clear_color);
+        self.framebuffer
+            .resize((new_width * new_height) as usize, self.clear_color);
+
+        self.resize_texture(new_width, new_height);
+    }
+
+    /// Recreate the texture with the new framebuffer size.
+    fn resize_texture(&mut self, new_width: u32, new_height: u32) {
+        // miniquad's `texture_resize` is currently unimplemented on Metal backend so we're doing this awkward dance
+
+        let format = self.texture_format();
+
+        if let Some(gpu) = &mut self.gpu {
+            gpu.backend.delete_texture(gpu.texture());
+
+            let new_texture = gpu.backend.new_render_texture(Self::texture_params(
+                new_width,
+                new_height,
+                self.texture_filter,
+                format,
+            ));
+            gpu.set_texture(new_texture);
+        }
+
+        self.buf_width = new_width;
+        self.buf_height = new_height;
+
+        self.update_quad();
+    }
+
+    /// Set how the framebuffer is scaled to the window.
+    ///
+    /// The default is [`ScaleMode::Stretch`].
+    #[inline]
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        self.scale_mode = mode;
+        self.update_quad();
+    }
+
+    /// Returns how the framebuffer is scaled to the window.
+    #[inline]
+    pub fn get_scale_mode(&self) -> ScaleMode {
+        self.scale_mode
+    }
+
+    /// Draw the framebuffer to a rectangle `(x, y, width, height)` of the window (in screen pixels, origin at the top left)
+    /// or to the whole window with `None` (the default), e.g. to leave room for side panels.
+    ///
+    /// The [`ScaleMode`] applies inside the rectangle, and the mouse position in framebuffer pixels accounts for it.
+    /// The rest of the window is black. The rectangle is kept as is when the window is resized.
+    #[inline]
+    pub fn set_viewport(&mut self, rect: Option<(i32, i32, u32, u32)>) {
+        self.viewport = rect;
+        self.update_quad();
+    }
+
+    /// Returns the rectangle of the window the framebuffer is drawn to or `None` if it's the whole window.
+    #[inline]
+    pub fn get_viewport(&self) -> Option<(i32, i32, u32, u32)> {
+        self.viewport
+    }
+
+    /// Set how drawn pixels are combined with the pixels already in the framebuffer.
+    ///
+    /// Affects all drawing functions except [`DrawTarget::draw_pixel_blended()`] and [`DrawTarget::flood_fill()`].
+    /// The default is [`BlendMode::Replace`].
+    #[inline]
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Returns the current blend mode.
+    #[inline]
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Restrict drawing to a rectangle `(x, y, width, height)` or allow drawing to the whole framebuffer with `None`.
+    ///
+    /// All drawing functions skip the pixels outside the clip rectangle.
+    /// [`Context::clear()`] and direct framebuffer access aren't affected.
+    #[inline]
+    pub fn set_clip(&mut self, rect: Option<(i32, i32, u32, u32)>) {
+        self.clip = rect;
+    }
+
+    /// Returns the current clip rectangle.
+    #[inline]
+    pub fn get_clip(&self) -> Option<(i32, i32, u32, u32)> {
+        self.clip
+    }
+
+    /// Returns a writer that draws text at the text cursor, e.g. for debug output.
+    ///
+    /// Uses the [default font](Font::default) and [`Context::get_text_color()`].
+    /// The cursor advances as the text is written, so that consecutive writes continue where the last one stopped,
+    /// and goes back to the position set with [`Context::set_text_cursor()`] at the end of every frame.
+    /// Draws on the active layer like the [`DrawTarget`] functions.
+    ///
+    /// ```no_run
+    /// # use simple_pixels::Context;
+    /// use std::fmt::Write;
+    ///
+    /// # fn draw(ctx: &mut Context, score: u32) {
+    /// writeln!(ctx.text_cursor(), "score: {score}").unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn text_cursor(&mut self) -> TextWriter<'_, Self> {
+        let (x, y) = self.text_cursor;
+        let (left, color) = (self.text_origin.0, self.text_color);
+
+        TextWriter::new(self, x, y, color)
+            .left(left)
+            .on_drop(|ctx, position| ctx.text_cursor = position)
+    }
+
+    /// Move the text cursor used by [`Context::text_cursor()`] to (x, y).
+    ///
+    /// Wrapped and new lines start at `x`. The cursor is moved back here at the end of every frame.
+    /// The default is (0, 0).
+    #[inline]
+    pub fn set_text_cursor(&mut self, x: i32, y: i32) {
+        self.text_origin = (x, y);
+        self.text_cursor = (x, y);
+    }
+
+    /// Returns the text cursor position, where [`Context::text_cursor()`] will draw the next character.
+    #[inline]
+    pub fn get_text_cursor(&self) -> (i32, i32) {
+        self.text_cursor
+    }
+
+    /// Set the color of the text drawn with [`Context::text_cursor()`]. The default is white.
+    #[inline]
+    pub fn set_text_color(&mut self, color: RGBA8) {
+        self.text_color = color;
+    }
+
+    /// Returns the color of the text drawn with [`Context::text_cursor()`].
+    #[inline]
+    pub fn get_text_color(&self) -> RGBA8 {
+        self.text_color
+    }
+
+    /// Create an off-screen layer filled with transparent pixels.
+    ///
+    /// Layers live as long as the context. Make a layer active with [`Context::set_active_layer()`]
+    /// to draw on it instead of the framebuffer and draw it onto the active target with [`Context::draw_layer()`].
+    pub fn create_layer(&mut self, width: u32, height: u32) -> LayerId {
+        self.layers
+            .push(Canvas::new(width, height, RGBA8::new(0, 0, 0, 0)));
+
+        LayerId(self.layers.len() - 1)
+    }
+
+    /// Set the layer that the [`DrawTarget`] functions draw on, or draw on the framebuffer with `None` (the default).
+    ///
+    /// The clip rectangle and the blend mode apply to layers too, and so do [`Context::clear()`],
+    /// [`Context::buffer_width()`], [`Context::get_mut_draw_buffer()`] and similar functions.
+    /// Functions that are explicitly about the framebuffer (e.g. [`Context::snapshot()`], [`Context::save_png()`])
+    /// always use the framebuffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer wasn't created by this context.
+    #[inline]
+    pub fn set_active_layer(&mut self, id: Option<LayerId>) {
+        if let Some(id) = id {
+            assert!(id.0 < self.layers.len(), "invalid layer id");
+        }
+
+        self.active_layer = id;
+    }
+
+    /// Returns the layer that is drawn on, `None` means the framebuffer.
+    #[inline]
+    pub fn get_active_layer(&self) -> Option<LayerId> {
+        self.active_layer
+    }
+
+    /// Returns a layer's contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer wasn't created by this context.
+    #[inline]
+    pub fn get_layer(&self, id: LayerId) -> &Canvas {
+        &self.layers[id.0]
+    }
+
+    /// Returns a layer's contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer wasn't created by this context.
+    #[inline]
+    pub fn get_layer_mut(&mut self, id: LayerId) -> &mut Canvas {
+        &mut self.layers[id.0]
+    }
+
+    /// Draw a layer with its top left corner at (x, y) onto the active layer or the framebuffer,
+    /// like [`DrawTarget::draw_canvas()`].
+    ///
+    /// Drawing the active layer onto itself is allowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer wasn't created by this context.
+    pub fn draw_layer(&mut self, id: LayerId, x: i32, y: i32) {
+        if self.active_layer == Some(id) {
+            // the source has to stay unchanged while it's drawn onto itself
+            let layer = self.layers[id.0].clone();
+            self.draw_canvas(x, y, &layer);
+        } else {
+            // take the layer out so that it can be drawn while `self` is borrowed mutably
+            let layer =
+                std::mem::replace(&mut self.layers[id.0], Canvas::new(0, 0, RGBA8::default()));
+            self.draw_canvas(x, y, &layer);
+            self.layers[id.0] = layer;
+        }
+    }
+
+    /// Clear the active layer or the framebuffer if there isn't one.
+    ///
+    /// The framebuffer is cleared with the current [`Context::clear_color()`], layers are cleared to transparent
+    /// like when they're created. Ignores the clip rectangle and the blend mode.
+    #[inline]
+    pub fn clear(&mut self) {
+        let color = self.target_clear_color();
+        draw::fill_buffer(self.get_mut_draw_buffer(), color);
+    }
+
+    /// Clear a rectangle of the active layer or the framebuffer if there isn't one, like [`Context::clear()`].
+    ///
+    /// Like [`Context::clear()`], ignores the clip rectangle and the blend mode.
+    /// Does not panic if a part of the rectangle isn't on screen, just clears the part that is.
+    pub fn clear_rect(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        let color = self.target_clear_color();
+        let (buf_width, buf_height) = (self.buffer_width() as i64, self.buffer_height() as i64);
+        let (x, y) = (x as i64, y as i64);
+
+        let left = x.clamp(0, buf_width) as usize;
+        let top = y.clamp(0, buf_height) as usize;
+        let right = (x + width as i64).clamp(0, buf_width) as usize;
+        let bottom = (y + height as i64).clamp(0, buf_height) as usize;
+
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let rows =
+            &mut self.get_mut_draw_buffer()[top * buf_width as usize..bottom * buf_width as usize];
+
+        // full-width rows are contiguous, so they can be cleared at once
+        if right - left == buf_width as usize {
+            draw::fill_buffer(rows, color);
+            return;
+        }
+
+        for row in rows.chunks_exact_mut(buf_width as usize) {
+            row[left..right].fill(color);
+        }
+    }
+
+    /// The color that [`Context::clear()`] fills the active layer or the framebuffer with.
+    #[inline]
+    fn target_clear_color(&self) -> RGBA8 {
+        match self.active_layer {
+            Some(_) => RGBA8::new(0, 0, 0, 0),
+            None => self.clear_color,
+        }
+    }
+
+    /// Returns the contents of the active layer or the framebuffer if there isn't one.
+    #[inline]
+    pub fn get_draw_buffer(&self) -> &[RGBA8] {
+        match self.active_layer {
+            Some(id) => self.layers[id.0].pixels(),
+            None => &self.framebuffer,
+        }
+    }
+
+    /// Returns the contents of the active layer or the framebuffer if there isn't one.
+    ///
+    /// Can be used for drawing.
+    #[inline]
+    pub fn get_mut_draw_buffer(&mut self) -> &mut [RGBA8] {
+        match self.active_layer {
+            Some(id) => self.layers[id.0].pixels_mut(),
+            None => &mut self.framebuffer,
+        }
+    }
+
+    /// Returns a copy of the framebuffer's contents as `(pixels, width, height)`.
+    #[inline]
+    pub fn snapshot(&self) -> (Vec<RGBA8>, u32, u32) {
+        (self.framebuffer.clone(), self.buf_width, self.buf_height)
+    }
+
+    /// Render the framebuffer with the post-processing shader (see [`Context::set_post_shader()`]) and read the result back.
+    ///
+    /// The shader is drawn to an offscreen texture the size of the framebuffer instead of the window,
+    /// so the result is `buffer_width * buffer_height` pixels (row-major order) and doesn't depend on the window size,
+    /// the [`ScaleMode`] or the viewport. The framebuffer is uploaded first, so the current contents are used.
+    /// With the default shader, this is the framebuffer as it ended up on the GPU (fully opaque with [`PixelFormat::Rgb8`]).
+    ///
+    /// This is slow (it waits for the GPU) and is mainly meant for testing and debugging.
+    /// Returns `None` on Metal, which doesn't support reading textures back, and on a headless context.
+    pub fn read_screen_pixels(&mut self) -> Option<Vec<RGBA8>> {
+        if self.gpu.as_ref()?.backend.info().backend == Backend::Metal {
+            return None;
+        }
+
+        if self.framebuffer.is_empty() {
+            return Some(Vec::new());
+        }
+
+        self.upload_framebuffer();
+
+        let (width, height) = (self.buf_width, self.buf_height);
+        let gpu = self.gpu.as_mut()?;
+        let backend = &mut *gpu.backend;
+
+        let target = backend.new_render_texture(Self::texture_params(
+            width,
+            height,
+            FilterMode::Nearest,
+            TextureFormat::RGBA8,
+        ));
+        let pass = backend.new_render_pass(target, None);
+
+        // the post-processing pass with the framebuffer covering the whole target
+        let vertex_buffer = backend.new_buffer(
+            BufferType::VertexBuffer,
+            BufferUsage::Immutable,
+            BufferSource::slice(&Self::quad_vertices(-1., -1., 1., 1.)),
+        );
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer: gpu.bindings.index_buffer,
+            images: gpu.bindings.images.clone(),
+        };
+
+        backend.begin_pass(Some(pass), PassAction::clear_color(0., 0., 0., 1.));
+        backend.apply_pipeline(&gpu.pipeline);
+        backend.apply_bindings(&bindings);
+
+        if let Some(apply_uniforms) = &self.post_uniforms {
+            apply_uniforms(backend);
+        }
+
+        backend.draw(0, 6, 1);
+        backend.end_render_pass();
+
+        let mut pixels = vec![RGBA8::default(); self.framebuffer.len()];
+        backend.texture_read_pixels(target, pixels.as_bytes_mut());
+
+        // also deletes the texture
+        backend.delete_render_pass(pass);
+        backend.delete_buffer(vertex_buffer);
+
+        // the rendered texture starts at the bottom row
+        let pixels = pixels
+            .chunks_exact(width as usize)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Some(pixels)
+    }
+
+    /// Save the framebuffer's contents to a PNG file.
+    ///
+    /// Always returns [`PngError::Unsupported`] on web.
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), PngError> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = path;
+            Err(PngError::Unsupported)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+            let mut encoder = png::Encoder::new(file, self.buf_width, self.buf_height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(self.framebuffer.as_bytes())?;
+            writer.finish()?;
+
+            Ok(())
+        }
+    }
+
+    /// Get the draw framebuffer as a [`simple_blit::GenericSurface`].
+    #[inline]
+    pub fn as_surface(&self) -> GenericSurface<&[RGBA8], RGBA8> {
+        GenericSurface::new(
+            &self.framebuffer[..],
+            simple_blit::size(self.buf_width, self.buf_height),
+        )
+        .unwrap()
+    }
+
+    /// Get the draw framebuffer as a mutable [`simple_blit::GenericSurface`].
+    #[inline]
+    pub fn as_mut_surface(&mut self) -> GenericSurface<&mut [RGBA8], RGBA8> {
+        GenericSurface::new(
+            &mut self.framebuffer[..],
+            simple_blit::size(self.buf_width, self.buf_height),
+        )
+        .unwrap()
+    }
+
+    /// Returns `true` if the rectangle is completely inside the framebuffer.
+    #[inline]
+    fn contains_rect(&self, x: u32, y: u32, width: u32, height: u32) -> bool {
+        x as u64 + width as u64 <= self.buf_width as u64
+            && y as u64 + height as u64 <= self.buf_height as u64
+    }
+
+    /// Get a rectangle of the draw framebuffer as a [`simple_blit::SubSurface`],
+    /// e.g. to blit from it with your own code.
+    ///
+    /// Returns `None` if the rectangle isn't completely inside the framebuffer.
+    #[inline]
+    pub fn sub_surface(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Option<SubSurface<GenericSurface<&[RGBA8], RGBA8>, RGBA8>> {
+        self.contains_rect(x, y, width, height).then(|| {
+            SubSurface::new(
+                self.as_surface(),
+                simple_blit::point(x, y),
+                simple_blit::size(width, height),
+            )
+        })
+    }
+
+    /// Get a rectangle of the draw framebuffer as a mutable [`simple_blit::SubSurface`],
+    /// e.g. to blit to it with your own code without touching the rest of the framebuffer.
+    ///
+    /// Returns `None` if the rectangle isn't completely inside the framebuffer.
+    #[inline]
+    pub fn sub_surface_mut(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Option<SubSurface<GenericSurface<&mut [RGBA8], RGBA8>, RGBA8>> {
+        self.contains_rect(x, y, width, height).then(|| {
+            SubSurface::new(
+                self.as_mut_surface(),
+                simple_blit::point(x, y),
+                simple_blit::size(width, height),
+            )
+        })
+    }
+
+    /// Set the filter for the texture that is used for rendering.
+    ///
+    /// The filter is kept when the framebuffer is resized. The default is [`FilterMode::Nearest`].
+    #[inline]
+    pub fn set_texture_filter(&mut self, filter: FilterMode) {
+        self.texture_filter = filter;
+
+        if let Some(gpu) = &mut self.gpu {
+            gpu.backend
+                .texture_set_filter(gpu.texture(), filter, MipmapFilterMode::None);
+        }
+    }
+
+    /// Set how the framebuffer is stored on the GPU.
+    ///
+    /// The default is [`PixelFormat::Rgba8`].
+    pub fn set_pixel_format(&mut self, format: PixelFormat) {
+        if format != self.pixel_format {
+            self.pixel_format = format;
+            self.upload_buffer = Vec::new();
+            self.resize_texture(self.buf_width, self.buf_height);
+        }
+    }
+
+    /// Returns how the framebuffer is stored on the GPU.
+    #[inline]
+    pub fn get_pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// The texture format matching the pixel format on the current backend.
+    fn texture_format(&self) -> TextureFormat {
+        match self.pixel_format {
+            PixelFormat::Rgb8
+                if self
+                    .gpu
+                    .as_ref()
+                    .is_some_and(|gpu| gpu.backend.info().backend != Backend::Metal) =>
+            {
+                TextureFormat::RGB8
+            }
+            _ => TextureFormat::RGBA8,
+        }
+    }
+
+    /// Upload the framebuffer to the texture, converting it to the texture format.
+    fn upload_framebuffer(&mut self) {
+        let format = self.texture_format();
+
+        let Some(gpu) = &mut self.gpu else {
+            return;
+        };
+
+        match format {
+            TextureFormat::RGB8 => {
+                self.upload_buffer.clear();
+                self.upload_buffer.extend(
+                    self.framebuffer
+                        .iter()
+                        .flat_map(|pix| [pix.r, pix.g, pix.b]),
+                );
+
+                gpu.backend
+                    .texture_update(gpu.texture(), &self.upload_buffer);
+            }
+            _ => gpu
+                .backend
+                .texture_update(gpu.texture(), self.framebuffer.as_bytes()),
+        }
+    }
+
+    /// Returns the filter for the texture that is used for rendering.
+    #[inline]
+    pub fn get_texture_filter(&self) -> FilterMode {
+        self.texture_filter
+    }
+
+    /// Get the underlying [`RenderingBackend`](https://docs.rs/miniquad/latest/miniquad/graphics/trait.RenderingBackend.html).
+    ///
+    /// Panics on a headless context.
+    #[inline]
+    pub fn get_rendering_backend(&self) -> &dyn RenderingBackend {
+        &*self.gpu.as_ref().expect(NO_GPU).backend
+    }
+
+    /// Get the underlying [`RenderingBackend`](https://docs.rs/miniquad/latest/miniquad/graphics/trait.RenderingBackend.html).
+    ///
+    /// Panics on a headless context.
+    #[inline]
+    pub fn get_mut_rendering_backend(&mut self) -> &mut dyn RenderingBackend {
+        &mut *self.gpu.as_mut().expect(NO_GPU).backend
+    }
+}
+
+impl DrawTarget for Context {
+    #[inline]
+    fn buffer_width(&self) -> u32 {
+        Context::buffer_width(self)
+    }
+
+    #[inline]
+    fn buffer_height(&self) -> u32 {
+        Context::buffer_height(self)
+    }
+
+    #[inline]
+    fn get_draw_buffer(&self) -> &[RGBA8] {
+        Context::get_draw_buffer(self)
+    }
+
+    #[inline]
+    fn get_mut_draw_buffer(&mut self) -> &mut [RGBA8] {
+        Context::get_mut_draw_buffer(self)
+    }
+
+    #[inline]
+    fn get_clip(&self) -> Option<(i32, i32, u32, u32)> {
+        self.clip
+    }
+
+    #[inline]
+    fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    #[inline]
+    fn blur(&mut self, radius: u32) {
+        // the scratch buffer is kept so that blurring every frame doesn't reallocate
+        let (buffer, width, height) = match self.active_layer {
+            Some(id) => {
+                let layer = &mut self.layers[id.0];
+                let (width, height) = (layer.width(), layer.height());
+
+                (layer.pixels_mut(), width, height)
+            }
+            None => (&mut self.framebuffer[..], self.buf_width, self.buf_height),
+        };
+
+        draw::box_blur(buffer, &mut self.blur_scratch, width, height, radius);
+    }
+}
+
+/// Panic message for GPU access on a headless context.
+const NO_GPU: &str = "a headless context has no rendering backend";
+
+/// How far (in screen coords) the mouse can move between two clicks for them to count as a double click.
+const DOUBLE_CLICK_DISTANCE: f32 = 4.;
+
+/// Maximum number of [`App::fixed_update()`] calls per frame.
+///
+/// This prevents the app from falling further and further behind if fixed updates take too long.
+pub const MAX_FIXED_UPDATES: u32 = 5;
+
+/// Application state.
+pub trait App {
+    /// Called once after the [`Context`] is created, before the first `update()`.
+    ///
+    /// The context is fully initialized at this point,
+    /// so [`Context::buffer_width()`], [`Context::display_width()`] etc. are valid here.
+    #[inline]
+    fn init(&mut self, _ctx: &mut Context) {}
+
+    /// Called every frame.
+    fn update(&mut self, ctx: &mut Context);
+
+    /// Called zero or more times every frame before `update()`, so that it runs once per fixed time step on average.
+    /// Only called if enabled with [`Context::set_fixed_timestep()`].
+    ///
+    /// At most [`MAX_FIXED_UPDATES`] calls are done per frame, the rest of the time is dropped.
+    #[inline]
+    fn fixed_update(&mut self, _ctx: &mut Context) {}
+
+    /// Called every frame after `update()`.
+    /// See <https://docs.rs/miniquad/latest/miniquad/trait.EventHandler.html#tymethod.update> for specifics.
+    ///
+    /// The input state is the same as in `update()`, e.g. [`Context::is_key_pressed()`] returns the same value in both.
+    fn draw(&mut self, ctx: &mut Context);
+
+    /// Called when the window is resized.
+    ///
+    /// `width` and `height` are the new display size (in screen coordinates).
+    /// [`Context::display_width()`] and [`Context::display_height()`] already return the new size here.
+    #[inline]
+    fn resize(&mut self, _ctx: &mut Context, _width: f32, _height: f32) {}
+
+    /// Called when the window is minimized or the app is put in the background.
+    ///
+    /// Only implemented on Android, X11 and web. On X11 and web it's also called when the window loses focus.
+    #[inline]
+    fn window_minimized(&mut self, _ctx: &mut Context) {}
+
+    /// Called when the window is restored after being minimized or the app is brought back from the background.
+    ///
+    /// Only implemented on Android, X11 and web. On X11 and web it's also called when the window gains focus.
+    #[inline]
+    fn window_restored(&mut self, _ctx: &mut Context) {}
+
+    /// Called when the window gains (`focused == true`) or loses focus.
+    ///
+    /// `miniquad` doesn't have dedicated focus events,
+    /// so this is called right after [`App::window_minimized()`] and [`App::window_restored()`]
+    /// and has the same platform limitations.
+    ///
+    /// When the window loses focus, all held keys and mouse buttons are [released](InputState::Released) before this is called,
+    /// since their key up events wouldn't be delivered to the window.
+    /// Gamepad buttons aren't released: gamepads are read independently of the window focus,
+    /// so their state stays correct.
+    #[inline]
+    fn focus(&mut self, _ctx: &mut Context, _focused: bool) {}
+
+    /// Called when the user tries to close the window or [`Context::quit()`] is called.
+    ///
+    /// Return `true` to allow the app to quit or `false` to cancel quitting
+    /// (e.g. to ask the user to save their work first). Quitting is allowed by default.
+    #[inline]
+    fn quit_requested(&mut self, _ctx: &mut Context) -> bool {
+        true
+    }
+
+    /// Called when files are dropped onto the window.
+    ///
+    /// Note that `miniquad` currently only supports this on web.
+    /// The paths there are virtual (just file names), the contents can be read with [`Context::dropped_file_bytes()`].
+    #[inline]
+    fn files_dropped(&mut self, _ctx: &mut Context, _paths: &[PathBuf]) {}
+
+    /// Called when a character is typed.
+    ///
+    /// `repeat` is `true` if the character comes from holding a key down.
+    #[inline]
+    fn char_input(&mut self, _ctx: &mut Context, _ch: char, _repeat: bool) {}
+
+    /// Called when a touch point starts, moves or ends.
+    ///
+    /// The position is in framebuffer pixels (see [`Context::active_touches()`]).
+    /// Touches are also reported as left mouse button events.
+    #[inline]
+    fn touch(&mut self, _ctx: &mut Context, _phase: TouchPhase, _id: u64, _x: f32, _y: f32) {}
+
+    /// Called when a gamepad button is pressed or released.
+    ///
+    /// `state` is never [`InputState::Down`].
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    fn gamepad_button(
+        &mut self,
+        _ctx: &mut Context,
+        _id: usize,
+        _button: GamepadButton,
+        _state: InputState,
+    ) {
+    }
+
+    /// Called when a gamepad axis changes its value.
+    #[cfg(feature = "gamepad")]
+    #[inline]
+    fn gamepad_axis(&mut self, _ctx: &mut Context, _id: usize, _axis: GamepadAxis, _value: f32) {}
+}
+
+struct Handler<S: App> {
+    ctx: Context,
+    state: S,
+}
+
+#[cfg(feature = "gamepad")]
+impl<S: App> Handler<S> {
+    fn poll_gamepads(&mut self) {
+        while let Some(gilrs::Event { id, event, .. }) =
+            self.ctx.gilrs.as_mut().and_then(|gilrs| gilrs.next_event())
+        {
+            let id = id.into();
+
+            match event {
+                gilrs::EventType::Connected => {
+                    self.ctx.gamepads.entry(id).or_default();
+                }
+                gilrs::EventType::Disconnected => {
+                    self.ctx.gamepads.remove(&id);
+                }
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    let gamepad = self.ctx.gamepads.entry(id).or_default();
+                    gamepad.buttons.insert(button, InputState::Pressed);
+
+                    self.state
+                        .gamepad_button(&mut self.ctx, id, button, InputState::Pressed);
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    let gamepad = self.ctx.gamepads.entry(id).or_default();
+                    gamepad.buttons.insert(button, InputState::Released);
+
+                    self.state
+                        .gamepad_button(&mut self.ctx, id, button, InputState::Released);
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    let gamepad = self.ctx.gamepads.entry(id).or_default();
+                    gamepad.axes.insert(axis, value);
+
+                    self.state.gamepad_axis(&mut self.ctx, id, axis, value);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(gilrs) = &mut self.ctx.gilrs {
+            gilrs.inc();
+        }
+    }
+}
+
+impl<S> EventHandler for Handler<S>
+where
+    S: App,
+{
+    fn update(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.ctx.reload_watched_shader();
+
+        let new_instant = miniquad::date::now();
+        self.ctx.delta_time = if self.ctx.time_paused {
+            0.
+        } else {
+            self.ctx.delta_override.unwrap_or_else(|| {
+                let delta = new_instant - self.ctx.instant;
+                self.ctx.max_delta.map_or(delta, |max| delta.min(max))
+            })
+        };
+        self.ctx.instant = new_instant;
+        self.ctx.elapsed += self.ctx.delta_time;
+
+        let (prev_x, prev_y) = self.ctx.prev_mouse_pos.unwrap_or(self.ctx.mouse_pos);
+        self.ctx.mouse_delta = (self.ctx.mouse_pos.0 - prev_x, self.ctx.mouse_pos.1 - prev_y);
+        self.ctx.prev_mouse_pos = Some(self.ctx.mouse_pos);
+
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepads();
+
+        self.ctx.frame_count += 1;
+
+        if self.ctx.delta_time > 0. {
+            let fps = (1. / self.ctx.delta_time) as f32;
+
+            self.ctx.fps = if self.ctx.fps == 0. {
+                fps
+            } else {
+                self.ctx.fps + self.ctx.fps_smoothing * (fps - self.ctx.fps)
+            };
+        }
+
+        // the framebuffer even if a layer is active
+        if self.ctx.auto_clear {
+            draw::fill_buffer(&mut self.ctx.framebuffer, self.ctx.clear_color);
+        }
+
+        if let Some(timestep) = self.ctx.fixed_timestep {
+            self.ctx.fixed_time_accumulator += self.ctx.delta_time;
+
+            let mut steps = 0;
+
+            while self.ctx.fixed_time_accumulator >= timestep && steps < MAX_FIXED_UPDATES {
+                self.state.fixed_update(&mut self.ctx);
+
+                self.ctx.fixed_time_accumulator -= timestep;
+                steps += 1;
+            }
+
+            self.ctx.fixed_time_accumulator %= timestep;
+        }
+
+        self.state.update(&mut self.ctx);
+    }
+
+    fn draw(&mut self) {
+        self.state.draw(&mut self.ctx);
+        self.ctx.end_frame_input();
+        self.ctx.text_cursor = self.ctx.text_origin;
+
+        self.ctx.upload_framebuffer();
+
+        if let Some(gpu) = &mut self.ctx.gpu {
+            gpu.backend
+                .begin_default_pass(PassAction::clear_color(0., 0., 0., 1.));
+
+            gpu.backend.apply_pipeline(&gpu.pipeline);
+            gpu.backend.apply_bindings(&gpu.bindings);
+
+            if let Some(apply_uniforms) = &self.ctx.post_uniforms {
+                apply_uniforms(&mut *gpu.backend);
+            }
+
+            gpu.backend.draw(0, 6, 1);
+
+            gpu.backend.end_render_pass();
+
+            gpu.backend.commit_frame();
+        }
+
+        self.ctx.wait_for_next_frame();
+    }
+
+    #[inline]
+    fn resize_event(&mut self, width: f32, height: f32) {
+        if !self.ctx.has_window() {
+            self.ctx.headless_screen_size = Some((width, height));
+        }
+
+        self.ctx.apply_framebuffer_scale();
+        self.ctx.update_quad();
+
+        self.state.resize(&mut self.ctx, width, height);
+    }
+
+    #[inline]
+    fn window_minimized_event(&mut self) {
+        self.ctx.release_all_input();
+
+        self.state.window_minimized(&mut self.ctx);
+        self.state.focus(&mut self.ctx, false);
+    }
+
+    #[inline]
+    fn window_restored_event(&mut self) {
+        self.state.window_restored(&mut self.ctx);
+        self.state.focus(&mut self.ctx, true);
+    }
+
+    #[inline]
+    fn quit_requested_event(&mut self) {
+        if !self.state.quit_requested(&mut self.ctx) {
+            window::cancel_quit();
+        }
+    }
+
+    fn files_dropped_event(&mut self) {
+        let paths: Vec<PathBuf> = (0..window::dropped_file_count())
+            .filter_map(window::dropped_file_path)
+            .collect();
+
+        self.state.files_dropped(&mut self.ctx, &paths);
+    }
+
+    #[inline]
+    fn key_down_event(&mut self, key_code: KeyCode, key_mods: KeyMods, repeat: bool) {
+        if !repeat {
+            self.ctx.keys.insert(key_code, InputState::Pressed);
+            self.ctx.last_key_pressed = Some(key_code);
+        }
+
+        self.ctx.update_key_mods(key_mods, Some(key_code));
+        self.ctx.events.push(InputEvent::KeyDown {
+            key: key_code,
+            mods: key_mods,
+            repeat,
+        });
+    }
+
+    #[inline]
+    fn key_up_event(&mut self, key_code: KeyCode, key_mods: KeyMods) {
+        self.ctx.keys.insert(key_code, InputState::Released);
+        self.ctx.update_key_mods(key_mods, Some(key_code));
+        self.ctx.events.push(InputEvent::KeyUp {
+            key: key_code,
+            mods: key_mods,
+        });
+    }
+
+    #[inline]
+    fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.ctx.mouse_buttons.insert(button, InputState::Pressed);
+        self.ctx.last_mouse_button_pressed = Some(button);
+        self.ctx.mouse_press_positions.insert(button, (x, y));
+        self.ctx.events.push(InputEvent::MouseDown { button, x, y });
+
+        let now = miniquad::date::now();
+
+        match self.ctx.mouse_last_presses.get(&button) {
+            Some(&(time, (last_x, last_y)))
+                if now - time <= self.ctx.double_click_threshold
+                    && (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE
+                    && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE =>
+            {
+                self.ctx.mouse_double_clicks.insert(button);
+                // so that a third click doesn't count as another double click
+                self.ctx.mouse_last_presses.remove(&button);
+            }
+            _ => {
+                self.ctx.mouse_last_presses.insert(button, (now, (x, y)));
+            }
+        }
+    }
+
+    #[inline]
+    fn mouse_button_up_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.ctx.mouse_buttons.insert(button, InputState::Released);
+        self.ctx.mouse_press_positions.remove(&button);
+        self.ctx.events.push(InputEvent::MouseUp { button, x, y });
+    }
+
+    #[inline]
+    fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        self.ctx.mouse_pos = (x, y);
+        self.ctx.events.push(InputEvent::MouseMove { x, y });
+    }
+
+    fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        let pos = self.ctx.screen_to_framebuffer(x, y);
+
+        match phase {
+            TouchPhase::Started | TouchPhase::Moved => {
+                self.ctx.touches.insert(id, pos);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.ctx.touches.remove(&id);
+            }
+        }
+
+        self.state.touch(&mut self.ctx, phase, id, pos.0, pos.1);
+
+        // keep emulating the mouse like miniquad does by default
+        match phase {
+            TouchPhase::Started => self.mouse_button_down_event(MouseButton::Left, x, y),
+            TouchPhase::Moved => self.mouse_motion_event(x, y),
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.mouse_button_up_event(MouseButton::Left, x, y)
+            }
+        }
+    }
+
+    #[inline]
+    fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+        // several wheel events can arrive during one frame
+        self.ctx.mouse_wheel.0 += x;
+        self.ctx.mouse_wheel.1 += y;
+        self.ctx.events.push(InputEvent::Wheel { x, y });
+    }
+
+    #[inline]
+    fn char_event(&mut self, character: char, key_mods: KeyMods, repeat: bool) {
+        self.ctx.update_key_mods(key_mods, None);
+        self.ctx.events.push(InputEvent::Char {
+            character,
+            mods: key_mods,
+            repeat,
+        });
+
+        self.state.char_input(&mut self.ctx, character, repeat);
+    }
+}
+
+/// Decode a PNG/JPEG/BMP image into RGBA pixels (row-major order).
+///
+/// Returns `(pixels, width, height)`. The result can be drawn with [`DrawTarget::draw_pixels()`].
+/// Images without an alpha channel are made fully opaque.
+#[cfg(feature = "image")]
+pub fn decode_image(bytes: &[u8]) -> Result<(Vec<RGBA8>, u32, u32), image::ImageError> {
+    use rgb::FromSlice;
+
+    let image = image::load_from_memory(bytes)?.into_rgba8();
+    let (width, height) = image.dimensions();
+
+    Ok((image.as_raw().as_rgba().to_vec(), width, height))
+}
+
+/// Start the application using provided config and state.
+///
+/// The config can be created with [`ConfigBuilder`].
+/// Vsync can be configured with `config.platform.swap_interval` (`0` is off, `1` is on).
+/// It's only a hint and `miniquad` currently only honors it on Linux (X11) and Windows.
+/// It can't be changed while the app is running.
+#[inline]
+pub fn start(config: Conf, mut state: impl App + 'static) {
+    let fullscreen = config.fullscreen;
+
+    miniquad::start(config, move || {
+        let mut ctx = Context::new(fullscreen);
+        state.init(&mut ctx);
+
+        Box::new(Handler { ctx, state })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestApp;
+
+    impl App for TestApp {
+        fn update(&mut self, _ctx: &mut Context) {}
+
+        fn draw(&mut self, _ctx: &mut Context) {}
+    }
+
+    /// A handler around a headless context, so that tests can feed it events and run frames.
+    fn handler(width: u32, height: u32) -> Handler<TestApp> {
+        Handler {
+            ctx: Context::new_headless(width, height),
+            state: TestApp,
+        }
+    }
+
+    /// Run one frame: `update()` and then `draw()`.
+    fn frame(handler: &mut Handler<TestApp>) {
+        handler.update();
+        handler.draw();
+    }
+
+    #[test]
+    fn headless_draws_to_framebuffer() {
+        let mut ctx = Context::new_headless(8, 4);
+        ctx.draw_rect(1, 1, 2, 2, color::RED);
+        ctx.draw_pixel(7, 3, color::WHITE);
+
+        assert_eq!(ctx.get_pixel(1, 1), Some(color::RED));
+        assert_eq!(ctx.get_pixel(2, 2), Some(color::RED));
+        assert_eq!(ctx.get_pixel(3, 1), Some(color::BLACK));
+        assert_eq!(ctx.get_pixel(7, 3), Some(color::WHITE));
+        assert_eq!(ctx.get_pixel(8, 3), None);
+
+        ctx.clear();
+        assert!(ctx.get_draw_buffer().iter().all(|&pix| pix == color::BLACK));
+    }
+
+    #[test]
+    fn headless_display_follows_framebuffer() {
+        let mut ctx = Context::new_headless(8, 4);
+        assert_eq!((ctx.display_width(), ctx.display_height()), (8., 4.));
+        assert_eq!(ctx.dpi_scale(), 1.);
+
+        ctx.set_framebuffer_size(3, 5);
+        assert_eq!((ctx.buffer_width(), ctx.buffer_height()), (3, 5));
+        assert_eq!(ctx.get_draw_buffer().len(), 15);
+        assert_eq!((ctx.display_width(), ctx.display_height()), (3., 5.));
+    }
+
+    #[test]
+    fn headless_window_functions_do_nothing() {
+        let mut ctx = Context::new_headless(2, 2);
+
+        ctx.set_window_size(100, 100);
+        ctx.show_mouse(false);
+        ctx.set_clipboard("text");
+        ctx.quit();
+
+        assert_eq!(ctx.get_clipboard(), None);
+        assert_eq!(ctx.read_screen_pixels(), None);
+    }
+
+    #[test]
+    fn headless_frames_run() {
+        let mut handler = handler(4, 4);
+        handler.ctx.draw_pixel(0, 0, color::RED);
+
+        frame(&mut handler);
+        frame(&mut handler);
+
+        assert_eq!(handler.ctx.frame_count(), 2);
+        assert_eq!(handler.ctx.get_pixel(0, 0), Some(color::RED));
+    }
+
+    #[test]
+    fn mouse_button_release() {
+        let mut handler = handler(4, 4);
+
+        handler.mouse_button_down_event(MouseButton::Left, 1., 1.);
+        assert_eq!(
+            handler.ctx.get_mouse_button_state(MouseButton::Left),
+            Some(InputState::Pressed)
+        );
+        frame(&mut handler);
+        assert!(handler.ctx.is_mouse_button_down(MouseButton::Left));
+
+        handler.mouse_button_up_event(MouseButton::Left, 1., 1.);
+        assert_eq!(
+            handler.ctx.get_mouse_button_state(MouseButton::Left),
+            Some(InputState::Released)
+        );
+        assert!(handler.ctx.is_mouse_button_released(MouseButton::Left));
+
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_mouse_button_state(MouseButton::Left), None);
+    }
+
+    #[test]
+    fn clip_rejects_outside_pixels() {
+        let mut ctx = Context::new_headless(8, 6);
+        ctx.set_clip(Some((2, 1, 3, 2)));
+
+        ctx.draw_rect(0, 0, 8, 6, color::WHITE);
+        ctx.draw_line(0, 5, 7, 0, color::WHITE);
+        ctx.draw_circle(4, 3, 3, color::WHITE);
+        ctx.draw_pixel(0, 0, color::WHITE);
+
+        for y in 0..6 {
+            for x in 0..8 {
+                let inside = (2..5).contains(&x) && (1..3).contains(&y);
+                let expected = if inside { color::WHITE } else { color::BLACK };
+                assert_eq!(ctx.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+
+        // the clip rectangle is intersected with the framebuffer
+        ctx.clear();
+        ctx.set_clip(Some((-4, 4, 6, 10)));
+        ctx.draw_rect(0, 0, 8, 6, color::WHITE);
+        let drawn = ctx
+            .get_draw_buffer()
+            .iter()
+            .filter(|&&pix| pix == color::WHITE);
+        assert_eq!(drawn.count(), 4);
+
+        ctx.set_clip(None);
+        ctx.draw_rect(0, 0, 8, 6, color::WHITE);
+        assert!(ctx.get_draw_buffer().iter().all(|&pix| pix == color::WHITE));
+    }
+
+    #[test]
+    fn triangle_seams() {
+        const COLOR: RGBA8 = RGBA8::new(100, 0, 0, 255);
+
+        /// Draw the quad as two triangles split along the a-c diagonal and add up the coverage.
+        fn draw_quad(quad: [(i32, i32); 4]) -> Context {
+            let [a, b, c, d] = quad;
+            let mut ctx = Context::new_headless(16, 16);
+            ctx.set_blend_mode(BlendMode::Add);
+            ctx.draw_triangle_filled(a, b, c, COLOR);
+            ctx.draw_triangle_filled(c, d, a, COLOR);
+            ctx
+        }
+
+        let quads = [
+            // the diagonal goes through pixel centers
+            [(0, 0), (8, 0), (8, 8), (0, 8)],
+            [(2, 1), (13, 3), (11, 14), (1, 9)],
+            [(8, 0), (15, 8), (8, 15), (0, 8)],
+        ];
+
+        for [a, b, c, d] in quads {
+            let split_ac = draw_quad([a, b, c, d]);
+            let split_bd = draw_quad([b, c, d, a]);
+
+            // no overlaps
+            for ctx in [&split_ac, &split_bd] {
+                assert!(ctx
+                    .get_draw_buffer()
+                    .iter()
+                    .all(|&pix| pix == color::BLACK || pix == COLOR));
+            }
+
+            // the same pixels are covered whichever way the quad is split, so there are no holes at the seam
+            assert_eq!(split_ac.get_draw_buffer(), split_bd.get_draw_buffer());
+        }
+
+        // an axis-aligned quad covers exactly the rectangle
+        let quad = draw_quad([(0, 0), (8, 0), (8, 8), (0, 8)]);
+        let mut rect = Context::new_headless(16, 16);
+        rect.draw_rect(0, 0, 8, 8, COLOR);
+        assert_eq!(quad.get_draw_buffer(), rect.get_draw_buffer());
+    }
+
+    #[test]
+    fn mouse_delta() {
+        let mut handler = handler(4, 4);
+
+        handler.mouse_motion_event(10., 20.);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_mouse_delta(), (0., 0.));
+
+        // several moves during a frame add up
+        handler.mouse_motion_event(12., 19.);
+        handler.mouse_motion_event(15., 16.);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_mouse_delta(), (5., -4.));
+
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_mouse_delta(), (0., 0.));
+    }
+
+    #[test]
+    fn mouse_double_click() {
+        let mut handler = handler(4, 4);
+        let click = |handler: &mut Handler<TestApp>, x: f32| {
+            handler.mouse_button_down_event(MouseButton::Left, x, 0.);
+            handler.mouse_button_up_event(MouseButton::Left, x, 0.);
+        };
+
+        click(&mut handler, 0.);
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+        frame(&mut handler);
+
+        click(&mut handler, 1.);
+        assert!(handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Right));
+        frame(&mut handler);
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+
+        // a third click starts over
+        click(&mut handler, 1.);
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+        frame(&mut handler);
+
+        // too far away
+        click(&mut handler, 1. + DOUBLE_CLICK_DISTANCE + 1.);
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+        frame(&mut handler);
+
+        // too late
+        handler
+            .ctx
+            .set_double_click_threshold(Duration::from_millis(400));
+        handler
+            .ctx
+            .mouse_last_presses
+            .get_mut(&MouseButton::Left)
+            .unwrap()
+            .0 -= 0.5;
+        click(&mut handler, 1. + DOUBLE_CLICK_DISTANCE + 1.);
+        assert!(!handler
+            .ctx
+            .is_mouse_button_double_clicked(MouseButton::Left));
+    }
+
+    #[test]
+    fn blend_modes() {
+        let dst = RGBA8::new(100, 200, 50, 255);
+        let src = RGBA8::new(200, 100, 255, 128);
+
+        for (mode, expected) in [
+            (BlendMode::Replace, src),
+            (BlendMode::AlphaBlend, RGBA8::new(150, 150, 153, 255)),
+            (BlendMode::Add, RGBA8::new(200, 250, 178, 255)),
+            (BlendMode::Multiply, RGBA8::new(89, 139, 50, 255)),
+        ] {
+            let mut ctx = Context::new_headless(1, 1);
+            ctx.draw_pixel(0, 0, dst);
+            ctx.set_blend_mode(mode);
+            ctx.draw_pixel(0, 0, src);
+
+            assert_eq!(ctx.get_pixel(0, 0), Some(expected), "{mode:?}");
+        }
+
+        // adding saturates
+        let mut ctx = Context::new_headless(1, 1);
+        ctx.draw_pixel(0, 0, dst);
+        ctx.set_blend_mode(BlendMode::Add);
+        ctx.draw_pixel(0, 0, RGBA8::new(200, 0, 0, 255));
+        assert_eq!(ctx.get_pixel(0, 0), Some(RGBA8::new(255, 200, 50, 255)));
+    }
+
+    #[test]
+    fn touch_points() {
+        let mut handler = handler(8, 8);
+
+        handler.touch_event(TouchPhase::Started, 1, 1., 2.);
+        handler.touch_event(TouchPhase::Started, 2, 5., 6.);
+        assert_eq!(handler.ctx.active_touches().len(), 2);
+        assert_eq!(handler.ctx.active_touches().get(&1), Some(&(1., 2.)));
+        assert_eq!(handler.ctx.active_touches().get(&2), Some(&(5., 6.)));
+        frame(&mut handler);
+
+        handler.touch_event(TouchPhase::Moved, 1, 3., 4.);
+        assert_eq!(handler.ctx.active_touches().get(&1), Some(&(3., 4.)));
+        assert_eq!(handler.ctx.active_touches().get(&2), Some(&(5., 6.)));
+
+        handler.touch_event(TouchPhase::Ended, 2, 5., 6.);
+        handler.touch_event(TouchPhase::Cancelled, 3, 0., 0.);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.active_touches().len(), 1);
+        assert_eq!(handler.ctx.active_touches().get(&1), Some(&(3., 4.)));
+
+        handler.touch_event(TouchPhase::Ended, 1, 3., 4.);
+        assert!(handler.ctx.active_touches().is_empty());
+    }
+
+    #[test]
+    fn last_key_pressed_lasts_one_frame() {
+        let mut handler = handler(4, 4);
+        assert_eq!(handler.ctx.get_last_key_pressed(), None);
+
+        handler.key_down_event(KeyCode::A, KeyMods::default(), false);
+        handler.key_down_event(KeyCode::B, KeyMods::default(), false);
+        handler.update();
+        assert_eq!(handler.ctx.get_last_key_pressed(), Some(KeyCode::B));
+        handler.draw();
+
+        // still held, but not just pressed
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_last_key_pressed(), None);
+        assert!(handler.ctx.is_key_down(KeyCode::B));
+    }
+
+    #[test]
+    fn char_events_drain_in_order() {
+        let mut handler = handler(4, 4);
+        let shift = KeyMods {
+            shift: true,
+            ..Default::default()
+        };
+
+        handler.char_event('a', KeyMods::default(), false);
+        handler.char_event('B', shift, false);
+
+        let events: Vec<_> = handler.ctx.drain_events().collect();
+        assert_eq!(
+            events,
+            [
+                InputEvent::Char {
+                    character: 'a',
+                    mods: KeyMods::default(),
+                    repeat: false,
+                },
+                InputEvent::Char {
+                    character: 'B',
+                    mods: shift,
+                    repeat: false,
+                },
+            ]
+        );
+        assert_eq!(handler.ctx.drain_events().count(), 0);
+
+        // events that aren't taken are dropped at the end of the frame
+        handler.char_event('c', KeyMods::default(), false);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn mouse_wheel_sums() {
+        let mut handler = handler(4, 4);
+
+        handler.mouse_wheel_event(0., 1.);
+        handler.mouse_wheel_event(0.5, 2.);
+        assert_eq!(handler.ctx.get_mouse_wheel(), (0.5, 3.));
+
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_mouse_wheel(), (0., 0.));
+    }
+
+    #[test]
+    fn max_delta_clamps_spikes() {
+        let mut handler = handler(4, 4);
+        handler.ctx.set_max_delta(Some(Duration::from_millis(100)));
+
+        // as if the app had been paused for 10 seconds
+        handler.ctx.instant -= 10.;
+        frame(&mut handler);
+        assert_eq!(handler.ctx.delta_time_secs(), 0.1);
+        assert_eq!(handler.ctx.elapsed, 0.1);
+
+        handler.ctx.set_max_delta(None);
+        handler.ctx.instant -= 10.;
+        frame(&mut handler);
+        assert!(handler.ctx.delta_time_secs() >= 10.);
+    }
+
+    #[test]
+    fn fullscreen_flag() {
+        let mut handler = handler(4, 4);
+        assert!(!handler.ctx.is_fullscreen());
+
+        handler.ctx.set_fullscreen(true);
+        frame(&mut handler);
+        assert!(handler.ctx.is_fullscreen());
+
+        handler.ctx.set_fullscreen(false);
+        frame(&mut handler);
+        assert!(!handler.ctx.is_fullscreen());
+    }
+
+    #[test]
+    fn auto_clear() {
+        let mut handler = handler(4, 4);
+        handler.ctx.draw_pixel(1, 1, color::RED);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_pixel(1, 1), Some(color::RED));
+
+        handler.ctx.set_auto_clear(true);
+        handler.ctx.clear_color(color::BLUE);
+        handler.ctx.draw_pixel(1, 1, color::RED);
+
+        // cleared before `App::update()`, so everything drawn in the previous frame is gone
+        handler.update();
+        assert!(handler
+            .ctx
+            .get_draw_buffer()
+            .iter()
+            .all(|&pix| pix == color::BLUE));
+        handler.draw();
+    }
+
+    #[test]
+    fn mouse_drag() {
+        let mut handler = handler(4, 4);
+        assert_eq!(handler.ctx.get_mouse_drag(MouseButton::Left), None);
+
+        handler.mouse_motion_event(10., 10.);
+        handler.mouse_button_down_event(MouseButton::Left, 10., 10.);
+        assert_eq!(
+            handler.ctx.get_mouse_drag(MouseButton::Left),
+            Some((0., 0.))
+        );
+        frame(&mut handler);
+
+        handler.mouse_motion_event(14., 7.);
+        frame(&mut handler);
+        assert_eq!(
+            handler.ctx.get_mouse_drag(MouseButton::Left),
+            Some((4., -3.))
+        );
+        assert_eq!(handler.ctx.get_mouse_drag(MouseButton::Right), None);
+
+        handler.mouse_button_up_event(MouseButton::Left, 14., 7.);
+        assert_eq!(handler.ctx.get_mouse_drag(MouseButton::Left), None);
+    }
+
+    #[test]
+    fn layers_draw_and_composite() {
+        let mut ctx = Context::new_headless(6, 4);
+        ctx.clear_color(color::BLUE);
+        ctx.clear();
+
+        let layer = ctx.create_layer(3, 2);
+        ctx.set_active_layer(Some(layer));
+        assert_eq!((ctx.buffer_width(), ctx.buffer_height()), (3, 2));
+        assert_eq!(ctx.get_draw_buffer().len(), 6);
+
+        ctx.draw_rect(0, 0, 2, 1, color::RED);
+        ctx.draw_pixel(2, 1, color::WHITE);
+        // outside the layer, even though it's inside the framebuffer
+        ctx.draw_pixel(4, 3, color::WHITE);
+
+        let clear = RGBA8::new(0, 0, 0, 0);
+        assert_eq!(
+            ctx.get_layer(layer).pixels(),
+            [color::RED, color::RED, clear, clear, clear, color::WHITE]
+        );
+
+        ctx.set_active_layer(None);
+        assert_eq!((ctx.buffer_width(), ctx.buffer_height()), (6, 4));
+        assert!(ctx.get_draw_buffer().iter().all(|&pix| pix == color::BLUE));
+
+        // transparent pixels of the layer let the framebuffer show through
+        ctx.set_blend_mode(BlendMode::AlphaBlend);
+        ctx.draw_layer(layer, 3, 2);
+        let b = color::BLUE;
+        #[rustfmt::skip]
+        assert_eq!(ctx.get_draw_buffer(), [
+            b, b, b, b, b, b,
+            b, b, b, b, b, b,
+            b, b, b, color::RED, color::RED, b,
+            b, b, b, b, b, color::WHITE,
+        ]);
+    }
+
+    #[test]
+    fn layers_clear() {
+        let mut handler = handler(4, 4);
+        handler.ctx.clear_color(color::BLUE);
+        handler.ctx.set_auto_clear(true);
+
+        let layer = handler.ctx.create_layer(2, 2);
+        handler.ctx.set_active_layer(Some(layer));
+        handler.ctx.draw_rect(0, 0, 2, 2, color::RED);
+
+        // auto-clear only clears the framebuffer
+        frame(&mut handler);
+        assert!(handler
+            .ctx
+            .framebuffer
+            .iter()
+            .all(|&pix| pix == color::BLUE));
+        assert!(handler
+            .ctx
+            .get_draw_buffer()
+            .iter()
+            .all(|&pix| pix == color::RED));
+
+        // clearing the active layer makes it transparent again
+        let clear = RGBA8::new(0, 0, 0, 0);
+        handler.ctx.clear_rect(1, 0, 5, 1);
+        assert_eq!(
+            handler.ctx.get_draw_buffer(),
+            [color::RED, clear, color::RED, color::RED]
+        );
+        handler.ctx.clear();
+        assert!(handler
+            .ctx
+            .get_draw_buffer()
+            .iter()
+            .all(|&pix| pix == clear));
+
+        handler.ctx.set_active_layer(None);
+        handler.ctx.draw_pixel(0, 0, color::RED);
+        handler.ctx.clear();
+        assert!(handler
+            .ctx
+            .get_draw_buffer()
+            .iter()
+            .all(|&pix| pix == color::BLUE));
+    }
+
+    #[test]
+    fn focus_loss_releases_input() {
+        let mut handler = handler(4, 4);
+        let shift = KeyMods {
+            shift: true,
+            ..Default::default()
+        };
+
+        handler.key_down_event(KeyCode::A, shift, false);
+        handler.mouse_button_down_event(MouseButton::Left, 1., 1.);
+        frame(&mut handler);
+        assert!(handler.ctx.is_key_down(KeyCode::A));
+
+        handler.window_minimized_event();
+        assert!(!handler.ctx.is_key_down(KeyCode::A));
+        assert!(handler.ctx.is_key_released(KeyCode::A));
+        assert!(handler.ctx.is_mouse_button_released(MouseButton::Left));
+        assert_eq!(handler.ctx.get_mouse_drag(MouseButton::Left), None);
+        assert_eq!(handler.ctx.key_mods, KeyMods::default());
+
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_key_state(KeyCode::A), None);
+        assert_eq!(handler.ctx.get_mouse_button_state(MouseButton::Left), None);
+    }
+
+    #[test]
+    fn interpolation_alpha() {
+        let mut handler = handler(4, 4);
+        handler
+            .ctx
+            .set_fixed_timestep(Some(Duration::from_millis(8)));
+        handler.ctx.set_fixed_delta_override(Some(0.02));
+
+        // two fixed steps, and half of a step is left over
+        frame(&mut handler);
+        assert!((handler.ctx.get_interpolation_alpha() - 0.5).abs() < 1e-6);
+
+        handler.ctx.fixed_time_accumulator = 0.006;
+        assert!((handler.ctx.get_interpolation_alpha() - 0.75).abs() < 1e-6);
+
+        handler.ctx.set_fixed_timestep(None);
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_interpolation_alpha(), 1.);
+    }
+
+    #[test]
+    fn key_pressed_with_modifiers() {
+        let mut handler = handler(4, 4);
+        let ctrl = KeyMods {
+            ctrl: true,
+            ..Default::default()
+        };
+        let ctrl_shift = KeyMods {
+            shift: true,
+            ..ctrl
+        };
+
+        handler.key_down_event(KeyCode::S, ctrl_shift, false);
+
+        // shift is held too, so it isn't exactly Ctrl+S
+        assert!(!handler.ctx.is_key_pressed_with(KeyCode::S, ctrl));
+        assert!(handler.ctx.is_key_pressed_with(KeyCode::S, ctrl_shift));
+        assert!(handler.ctx.is_key_pressed_with_any(KeyCode::S, ctrl));
+        assert!(handler.ctx.is_key_pressed_with_any(KeyCode::S, ctrl_shift));
+        assert!(handler
+            .ctx
+            .is_key_pressed_with_any(KeyCode::S, KeyMods::default()));
+
+        // a missing modifier never matches
+        let ctrl_alt = KeyMods { alt: true, ..ctrl };
+        assert!(!handler.ctx.is_key_pressed_with_any(KeyCode::S, ctrl_alt));
+        assert!(!handler.ctx.is_key_pressed_with_any(KeyCode::D, ctrl));
+
+        // only on the frame the key was pressed
+        frame(&mut handler);
+        assert!(!handler.ctx.is_key_pressed_with(KeyCode::S, ctrl_shift));
+        assert!(!handler.ctx.is_key_pressed_with_any(KeyCode::S, ctrl));
+    }
+
+    #[test]
+    fn viewport_mouse_mapping() {
+        fn pixel_at(handler: &mut Handler<TestApp>, x: f32, y: f32) -> Option<(u32, u32)> {
+            handler.mouse_motion_event(x, y);
+            handler.ctx.get_mouse_pixel()
+        }
+
+        let mut handler = handler(4, 2);
+        handler.ctx.set_viewport(Some((10, 20, 20, 7)));
+
+        // stretched by 5×3.5 over the whole viewport
+        handler.ctx.set_scale_mode(ScaleMode::Stretch);
+        assert_eq!(pixel_at(&mut handler, 10., 20.), Some((0, 0)));
+        assert_eq!(pixel_at(&mut handler, 15., 23.5), Some((1, 1)));
+        assert_eq!(pixel_at(&mut handler, 29.9, 26.9), Some((3, 1)));
+        assert_eq!(pixel_at(&mut handler, 9.9, 22.), None);
+        assert_eq!(pixel_at(&mut handler, 30.1, 22.), None);
+
+        // scaled by 3.5 to 14×7 and centered in the viewport, so the framebuffer starts at x = 13
+        handler.ctx.set_scale_mode(ScaleMode::Fit);
+        assert_eq!(pixel_at(&mut handler, 13., 20.), Some((0, 0)));
+        assert_eq!(pixel_at(&mut handler, 16.4, 23.4), Some((0, 0)));
+        assert_eq!(pixel_at(&mut handler, 16.6, 23.6), Some((1, 1)));
+        assert_eq!(pixel_at(&mut handler, 26.9, 26.9), Some((3, 1)));
+        // the black bars
+        assert_eq!(pixel_at(&mut handler, 12.9, 22.), None);
+        assert_eq!(pixel_at(&mut handler, 27.1, 22.), None);
+        // outside the viewport
+        assert_eq!(pixel_at(&mut handler, 20., 19.9), None);
+        assert_eq!(pixel_at(&mut handler, 20., 27.1), None);
+        assert_eq!(pixel_at(&mut handler, 1., 1.), None);
+
+        // scaled by 3 to 12×6, so the framebuffer starts at (14, 20) with bars on the left and right
+        // and a 1px bar at the bottom, since the centering offset is floored to 0 vertically
+        handler.ctx.set_scale_mode(ScaleMode::IntegerFit);
+        assert_eq!(pixel_at(&mut handler, 14., 20.), Some((0, 0)));
+        assert_eq!(pixel_at(&mut handler, 17.1, 23.1), Some((1, 1)));
+        assert_eq!(pixel_at(&mut handler, 25.9, 25.9), Some((3, 1)));
+        assert_eq!(pixel_at(&mut handler, 13.9, 22.), None);
+        assert_eq!(pixel_at(&mut handler, 26.1, 22.), None);
+        assert_eq!(pixel_at(&mut handler, 20., 26.1), None);
+        assert_eq!(pixel_at(&mut handler, 20., 19.9), None);
+    }
+
+    #[test]
+    fn letterboxed_mouse_mapping() {
+        fn pixel_at(handler: &mut Handler<TestApp>, x: f32, y: f32) -> Option<(u32, u32)> {
+            handler.mouse_motion_event(x, y);
+            handler.ctx.get_mouse_pixel()
+        }
+
+        // a 16:9 window over a 4:3 framebuffer, without a viewport
+        let mut handler = handler(8, 6);
+        handler.resize_event(32., 18.);
+
+        // stretched by 4×3 over the whole window
+        assert_eq!(pixel_at(&mut handler, 0., 0.), Some((0, 0)));
+        assert_eq!(pixel_at(&mut handler, 4., 3.), Some((1, 1)));
+        assert_eq!(pixel_at(&mut handler, 31.9, 17.9), Some((7, 5)));
+        assert_eq!(pixel_at(&mut handler, 32.1, 5.), None);
+
+        // scaled by 3 to 24×18 with bars on the left and right, so the framebuffer starts at x = 4
+        for mode in [ScaleMode::Fit, ScaleMode::IntegerFit] {
+            handler.ctx.set_scale_mode(mode);
+            assert_eq!(pixel_at(&mut handler, 4., 0.), Some((0, 0)));
+            assert_eq!(pixel_at(&mut handler, 7.1, 3.1), Some((1, 1)));
+            assert_eq!(pixel_at(&mut handler, 27.9, 17.9), Some((7, 5)));
+            assert_eq!(pixel_at(&mut handler, 3.9, 5.), None);
+            assert_eq!(pixel_at(&mut handler, 28.1, 5.), None);
+        }
+    }
+
+    #[test]
+    fn update_and_draw_see_the_same_input() {
+        /// Records whether a key was just pressed in every `update()` and `draw()`.
+        #[derive(Default)]
+        struct RecordingApp {
+            in_update: Vec<bool>,
+            in_draw: Vec<bool>,
+        }
+
+        impl App for RecordingApp {
+            fn update(&mut self, ctx: &mut Context) {
+                self.in_update.push(ctx.is_key_pressed(KeyCode::K));
+            }
+
+            fn draw(&mut self, ctx: &mut Context) {
+                self.in_draw.push(ctx.is_key_pressed(KeyCode::K));
+            }
+        }
+
+        let mut handler = Handler {
+            ctx: Context::new_headless(4, 4),
+            state: RecordingApp::default(),
+        };
+
+        handler.key_down_event(KeyCode::K, KeyMods::default(), false);
+        handler.update();
+        handler.draw();
+        assert_eq!(handler.state.in_update, [true]);
+        assert_eq!(handler.state.in_draw, [true]);
+
+        handler.update();
+        handler.draw();
+        assert_eq!(handler.state.in_update, [true, false]);
+        assert_eq!(handler.state.in_draw, [true, false]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn watched_shader_read_error() {
+        let mut ctx = Context::new_headless(2, 2);
+        assert_eq!(ctx.post_shader_error(), None);
+
+        // a directory exists but can't be read as a file
+        ctx.watch_post_shader(std::env::temp_dir(), vec![]);
+        assert!(ctx
+            .post_shader_error()
+            .is_some_and(|err| err.starts_with("failed to read")));
+
+        ctx.unwatch_post_shader();
+        assert_eq!(ctx.post_shader_error(), None);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn save_png_round_trip() {
+        let mut ctx = Context::new_headless(16, 4);
+        ctx.draw_gradient_h(
+            0,
+            0,
+            16,
+            4,
+            RGBA8::new(0, 50, 255, 255),
+            RGBA8::new(255, 200, 0, 64),
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("simple-pixels-test-{}.png", std::process::id()));
+        ctx.save_png(&path).unwrap();
+
+        let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let mut reader = png::Decoder::new(file).read_info().unwrap();
+        let mut bytes = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut bytes).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (16, 4));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(
+            &bytes[..info.buffer_size()],
+            ctx.get_draw_buffer().as_bytes()
+        );
+    }
+
+    /// A 2×2 8-bit grayscale PNG without alpha: 0, 85 / 170, 255.
+    #[cfg(feature = "image")]
+    #[rustfmt::skip]
+    const GRAYSCALE_PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x00, 0x00, 0x00, 0x00, 0x57, 0xdd, 0x52,
+        0xf8, 0x00, 0x00, 0x00, 0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60, 0x08, 0x65, 0x58,
+        0xf5, 0x1f, 0x00, 0x03, 0xad, 0x01, 0xff, 0x7a, 0x93, 0x84, 0x7f, 0x00, 0x00, 0x00, 0x00, 0x49,
+        0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    /// A 3×1 8-bit palette PNG without transparency: blue, red, (10, 200, 30).
+    #[cfg(feature = "image")]
+    #[rustfmt::skip]
+    const PALETTE_PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x08, 0x03, 0x00, 0x00, 0x00, 0x2c, 0x3e, 0xe4,
+        0x86, 0x00, 0x00, 0x00, 0x09, 0x50, 0x4c, 0x54, 0x45, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0x0a,
+        0xc8, 0x1e, 0xe0, 0x92, 0x06, 0x89, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda,
+        0x63, 0x60, 0x64, 0x60, 0x02, 0x00, 0x00, 0x09, 0x00, 0x04, 0x79, 0xda, 0x23, 0xd4, 0x00, 0x00,
+        0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn decode_image_expands_to_opaque_rgba() {
+        let gray = |value: u8| RGBA8::new(value, value, value, 255);
+        assert_eq!(
+            decode_image(GRAYSCALE_PNG).unwrap(),
+            (vec![gray(0), gray(85), gray(170), gray(255)], 2, 2)
+        );
+
+        assert_eq!(
+            decode_image(PALETTE_PNG).unwrap(),
+            (
+                vec![color::BLUE, color::RED, RGBA8::new(10, 200, 30, 255)],
+                3,
+                1
+            )
+        );
+
+        assert!(decode_image(&PALETTE_PNG[..40]).is_err());
+    }
+
+    #[test]
+    fn resize_framebuffer_preserving() {
+        let pattern = |x: i32, y: i32| RGBA8::new(x as u8 * 40, y as u8 * 40, 100, 255);
+        let mut ctx = Context::new_headless(4, 3);
+
+        for y in 0..3 {
+            for x in 0..4 {
+                ctx.draw_pixel(x, y, pattern(x, y));
+            }
+        }
+
+        // growing keeps everything and fills the new area
+        ctx.resize_framebuffer_preserving(6, 5, color::BLUE);
+        assert_eq!((ctx.buffer_width(), ctx.buffer_height()), (6, 5));
+
+        for y in 0..5 {
+            for x in 0..6 {
+                let expected = if x < 4 && y < 3 {
+                    pattern(x, y)
+                } else {
+                    color::BLUE
+                };
+                assert_eq!(ctx.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+
+        // shrinking keeps the top left part, even when only one side shrinks
+        ctx.resize_framebuffer_preserving(2, 4, color::RED);
+        assert_eq!(ctx.get_draw_buffer().len(), 8);
+
+        for y in 0..4 {
+            for x in 0..2 {
+                let expected = if y < 3 { pattern(x, y) } else { color::BLUE };
+                assert_eq!(ctx.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn fps_converges() {
+        let mut handler = handler(4, 4);
+
+        // start from 60 fps so that there's something to converge from
+        handler.ctx.set_fixed_delta_override(Some(1. / 60.));
+        frame(&mut handler);
+        assert!((handler.ctx.fps() - 60.).abs() < 1e-3);
+
+        handler.ctx.set_fixed_delta_override(Some(1. / 30.));
+        frame(&mut handler);
+        let first = handler.ctx.fps();
+        assert!(first > 30. && first < 60.);
+
+        for _ in 0..100 {
+            frame(&mut handler);
+        }
+        assert!((handler.ctx.fps() - 30.).abs() < 1e-3);
+
+        // no smoothing
+        handler.ctx.set_fixed_delta_override(Some(1. / 60.));
+        frame(&mut handler);
+        handler.ctx.set_fps_smoothing(1.);
+        handler.ctx.set_fixed_delta_override(Some(1. / 30.));
+        frame(&mut handler);
+        assert_eq!(handler.ctx.fps(), 30.);
+    }
+
+    #[test]
+    fn paused_time() {
+        let mut handler = handler(4, 4);
+        handler.ctx.set_fixed_delta_override(Some(0.02));
+        frame(&mut handler);
+        let elapsed = handler.ctx.elapsed_secs();
+
+        handler.ctx.pause_time();
+        for _ in 0..3 {
+            frame(&mut handler);
+            assert_eq!(handler.ctx.delta_time_secs(), 0.);
+            assert_eq!(handler.ctx.elapsed_secs(), elapsed);
+        }
+
+        handler.ctx.resume_time();
+        frame(&mut handler);
+        assert_eq!(handler.ctx.delta_time_secs(), 0.02);
+        assert_eq!(handler.ctx.elapsed_secs(), elapsed + 0.02);
+    }
+
+    #[cfg(feature = "gamepad")]
+    #[test]
+    fn gamepad_button_lifecycle() {
+        let mut handler = handler(4, 4);
+        let button = GamepadButton::South;
+
+        let gamepad = handler.ctx.gamepads.entry(0).or_default();
+        gamepad.buttons.insert(button, InputState::Pressed);
+        assert!(handler.ctx.is_gamepad_button_pressed(0, button));
+
+        frame(&mut handler);
+        assert_eq!(
+            handler.ctx.get_gamepad_button_state(0, button),
+            Some(InputState::Down)
+        );
+        assert!(handler.ctx.is_gamepad_button_down(0, button));
+
+        let gamepad = handler.ctx.gamepads.get_mut(&0).unwrap();
+        gamepad.buttons.insert(button, InputState::Released);
+        assert!(handler.ctx.is_gamepad_button_released(0, button));
+
+        frame(&mut handler);
+        assert_eq!(handler.ctx.get_gamepad_button_state(0, button), None);
+        assert!(!handler.ctx.is_gamepad_button_down(0, button));
+    }
 }
@@ -0,0 +1,55 @@
+//! Deterministic noise for procedural textures.
+
+/// Hash a lattice point into a value in `0.0..1.0`.
+#[inline]
+fn lattice_value(x: i64, y: i64, seed: u64) -> f32 {
+    // splitmix64 finalizer over the combined coordinates
+    let mut hash = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+
+    // the top 24 bits fit exactly in an `f32`
+    (hash >> 40) as f32 / (1 << 24) as f32
+}
+
+/// 2D value noise: random values at integer coordinates, smoothly interpolated in between.
+///
+/// Returns a value in `0.0..1.0`. The result only depends on the arguments, so the same seed always gives the same noise.
+///
+/// ```
+/// use simple_pixels::noise::value_noise_2d;
+///
+/// let value = value_noise_2d(3.25, -7.5, 42);
+///
+/// assert!((0.0..1.0).contains(&value));
+/// assert_eq!(value, value_noise_2d(3.25, -7.5, 42));
+/// assert_ne!(value, value_noise_2d(3.25, -7.5, 43));
+/// ```
+pub fn value_noise_2d(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    // smoothstep, so that there are no visible creases along the lattice lines
+    let fade = |t: f32| t * t * (3. - 2. * t);
+    let (tx, ty) = (fade(x - x0), fade(y - y0));
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let top = lerp(
+        lattice_value(ix, iy, seed),
+        lattice_value(ix + 1, iy, seed),
+        tx,
+    );
+    let bottom = lerp(
+        lattice_value(ix, iy + 1, seed),
+        lattice_value(ix + 1, iy + 1, seed),
+        tx,
+    );
+
+    // rounding can push the result up to exactly 1.0
+    lerp(top, bottom, ty).min(1. - f32::EPSILON / 2.)
+}
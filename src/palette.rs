@@ -0,0 +1,66 @@
+use rgb::RGBA8;
+use rustc_hash::FxHashMap;
+
+/// A palette of up to 256 colors.
+///
+/// ```
+/// # use simple_pixels::{color, rgb::RGBA8, Palette};
+/// let palette = Palette::new(vec![color::BLACK, color::WHITE]).unwrap();
+///
+/// assert_eq!(palette.nearest_index(RGBA8::new(40, 50, 60, 255)), 0);
+/// assert_eq!(palette.nearest_index(RGBA8::new(200, 190, 180, 255)), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<RGBA8>,
+}
+
+impl Palette {
+    /// Create a palette from a list of colors.
+    ///
+    /// Returns `None` if the list is empty or has more than 256 colors.
+    #[inline]
+    pub fn new(colors: Vec<RGBA8>) -> Option<Self> {
+        (1..=256).contains(&colors.len()).then_some(Self { colors })
+    }
+
+    /// Returns the colors of the palette.
+    #[inline]
+    pub fn colors(&self) -> &[RGBA8] {
+        &self.colors
+    }
+
+    /// Returns the index of the color closest to `color` (by Euclidean distance in RGB, the alpha is ignored).
+    ///
+    /// The first one wins if several colors are equally close.
+    pub fn nearest_index(&self, color: RGBA8) -> u8 {
+        let distance = |entry: &RGBA8| {
+            let diff = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+
+            diff(entry.r, color.r) + diff(entry.g, color.g) + diff(entry.b, color.b)
+        };
+
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| distance(entry))
+            .map_or(0, |(idx, _)| idx as u8)
+    }
+
+    /// Returns the color closest to `color`, see [`Palette::nearest_index()`].
+    #[inline]
+    pub fn nearest(&self, color: RGBA8) -> RGBA8 {
+        self.colors[self.nearest_index(color) as usize]
+    }
+
+    /// Replace every pixel with the closest palette color.
+    pub(crate) fn apply(&self, pixels: &mut [RGBA8]) {
+        // frames usually have few distinct colors, so searching for each one once is much faster
+        let mut cache = FxHashMap::default();
+
+        for pix in pixels {
+            let key = RGBA8 { a: 0, ..*pix };
+            *pix = *cache.entry(key).or_insert_with(|| self.nearest(key));
+        }
+    }
+}
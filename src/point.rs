@@ -0,0 +1,99 @@
+/// A point with `f32` coordinates, e.g. a position in screen coords.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    /// The x coordinate.
+    pub x: f32,
+    /// The y coordinate.
+    pub y: f32,
+}
+
+impl Point {
+    /// Create a point.
+    #[inline]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns `true` if the point is inside the rectangle.
+    ///
+    /// The top and left edges are inside, the bottom and right ones aren't.
+    ///
+    /// ```
+    /// # use simple_pixels::Point;
+    /// assert!(Point::new(10., 20.).is_inside(10., 20., 5., 5.));
+    /// assert!(Point::new(14.9, 24.9).is_inside(10., 20., 5., 5.));
+    /// assert!(!Point::new(15., 22.).is_inside(10., 20., 5., 5.));
+    /// assert!(!Point::new(12., 25.).is_inside(10., 20., 5., 5.));
+    /// assert!(!Point::new(9.9, 22.).is_inside(10., 20., 5., 5.));
+    /// ```
+    #[inline]
+    pub fn is_inside(self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        self.x >= x && self.y >= y && self.x < x + width && self.y < y + height
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    #[inline]
+    fn from((x, y): (f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Point> for (f32, f32) {
+    #[inline]
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+/// A point with `i32` coordinates, e.g. a pixel position in the framebuffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IPoint {
+    /// The x coordinate.
+    pub x: i32,
+    /// The y coordinate.
+    pub y: i32,
+}
+
+impl IPoint {
+    /// Create a point.
+    #[inline]
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns `true` if the point is inside the rectangle.
+    ///
+    /// The top and left edges are inside, the bottom and right ones aren't,
+    /// so a `width`×`height` rectangle contains exactly `width * height` points.
+    ///
+    /// ```
+    /// # use simple_pixels::IPoint;
+    /// assert!(IPoint::new(10, 20).is_inside(10, 20, 5, 5));
+    /// assert!(IPoint::new(14, 24).is_inside(10, 20, 5, 5));
+    /// assert!(!IPoint::new(15, 22).is_inside(10, 20, 5, 5));
+    /// assert!(!IPoint::new(12, 25).is_inside(10, 20, 5, 5));
+    /// assert!(!IPoint::new(9, 22).is_inside(10, 20, 5, 5));
+    /// assert!(!IPoint::new(10, 20).is_inside(10, 20, 0, 5));
+    /// ```
+    #[inline]
+    pub fn is_inside(self, x: i32, y: i32, width: u32, height: u32) -> bool {
+        let (px, py, x, y) = (self.x as i64, self.y as i64, x as i64, y as i64);
+
+        px >= x && py >= y && px < x + width as i64 && py < y + height as i64
+    }
+}
+
+impl From<(i32, i32)> for IPoint {
+    #[inline]
+    fn from((x, y): (i32, i32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<IPoint> for (i32, i32) {
+    #[inline]
+    fn from(point: IPoint) -> Self {
+        (point.x, point.y)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{font, DrawTarget, Font};
+use rgb::RGBA8;
+
+/// Draws text written with [`write!`] and [`writeln!`] at a cursor that advances with every character.
+///
+/// Lines wrap at the right edge of the buffer and continue at the cursor's starting column.
+/// See [`Context::text_cursor()`](crate::Context::text_cursor) for a cursor that lives across frames.
+///
+/// ```
+/// # use simple_pixels::{color, Canvas, DrawTarget, TextWriter};
+/// use std::fmt::Write;
+///
+/// let mut canvas = Canvas::new(64, 32, color::BLACK);
+/// let mut cursor = TextWriter::new(&mut canvas, 4, 2, color::WHITE);
+///
+/// writeln!(cursor, "_").unwrap();
+/// write!(cursor, "{}", '_').unwrap();
+/// assert_eq!(cursor.position(), (12, 10));
+///
+/// drop(cursor);
+///
+/// // the underscore is the bottom row of the glyph
+/// assert_eq!(canvas.get_pixel(4, 9), Some(color::WHITE));
+/// assert_eq!(canvas.get_pixel(4, 17), Some(color::WHITE));
+/// assert_eq!(canvas.get_pixel(12, 17), Some(color::BLACK));
+/// ```
+pub struct TextWriter<'a, T: DrawTarget + ?Sized> {
+    target: &'a mut T,
+    font: &'a Font,
+    color: RGBA8,
+    left: i32,
+    x: i32,
+    y: i32,
+    /// Called with the final position when the writer is dropped.
+    on_drop: fn(&mut T, (i32, i32)),
+}
+
+impl<'a, T: DrawTarget + ?Sized> TextWriter<'a, T> {
+    /// Create a writer with the cursor at (x, y) using the [default font](Font::default).
+    #[inline]
+    pub fn new(target: &'a mut T, x: i32, y: i32, color: RGBA8) -> Self {
+        Self::with_font(target, x, y, color, font::default_font())
+    }
+
+    /// Create a writer with the cursor at (x, y) using `font`.
+    #[inline]
+    pub fn with_font(target: &'a mut T, x: i32, y: i32, color: RGBA8, font: &'a Font) -> Self {
+        Self {
+            target,
+            font,
+            color,
+            left: x,
+            x,
+            y,
+            on_drop: |_, _| {},
+        }
+    }
+
+    /// Set the column that new lines start at.
+    #[inline]
+    pub(crate) fn left(mut self, left: i32) -> Self {
+        self.left = left;
+        self
+    }
+
+    /// Set the function that is called with the final cursor position when the writer is dropped.
+    #[inline]
+    pub(crate) fn on_drop(mut self, on_drop: fn(&mut T, (i32, i32))) -> Self {
+        self.on_drop = on_drop;
+        self
+    }
+
+    /// Returns the cursor position, where the next character will be drawn.
+    #[inline]
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    #[inline]
+    fn new_line(&mut self) {
+        self.x = self.left;
+        self.y = self.y.saturating_add(self.font.glyph_height() as i32);
+    }
+}
+
+impl<T: DrawTarget + ?Sized> std::fmt::Write for TextWriter<'_, T> {
+    fn write_str(&mut self, text: &str) -> std::fmt::Result {
+        let glyph_width = self.font.glyph_width() as i32;
+        let right = self.target.buffer_width() as i64;
+        let left = self.left;
+
+        for ch in text.chars() {
+            match ch {
+                '\n' => self.new_line(),
+                '\r' => {}
+                _ => {
+                    let width = |x: i32| {
+                        let column = ((x - left) / glyph_width) as u32;
+                        (font::advance(column, ch) - column) as i32 * glyph_width
+                    };
+
+                    // wrap unless the character wouldn't fit on an empty line either
+                    if self.x > self.left && self.x as i64 + width(self.x) as i64 > right {
+                        self.new_line();
+                    }
+
+                    let width = width(self.x);
+
+                    if ch != '\t' {
+                        let mut buf = [0; 4];
+                        self.target.draw_text(
+                            self.x,
+                            self.y,
+                            ch.encode_utf8(&mut buf),
+                            self.color,
+                            self.font,
+                        );
+                    }
+
+                    self.x = self.x.saturating_add(width);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: DrawTarget + ?Sized> Drop for TextWriter<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        (self.on_drop)(self.target, (self.x, self.y));
+    }
+}